@@ -29,6 +29,28 @@ make_request() {
     echo
 }
 
+# Rejestracja grupy przyjmuje tylko nazwę, e-mail i hasło; preferencje ustawia zalogowana grupa
+register_group() {
+    local name=$1
+    local prefs=$2
+    local description=$3
+
+    make_request POST "/group" \
+        "{\"name\":\"$name\",\"email\":\"$name@test.com\",\"password\":\"test\"}" \
+        "$description"
+
+    if [ -n "$prefs" ]; then
+        local session=$(curl -s -X POST "http://localhost:3000/login/group" \
+            -H "Content-Type: application/json" \
+            -d "{\"email\":\"$name@test.com\",\"password\":\"test\"}" \
+            | grep -o '"session_id":"[^"]*"' | cut -d'"' -f4)
+        local tiers=$(echo $prefs | sed 's/\([^ ]*\)/["\1"]/g; s/ /,/g')
+        make_request POST "/group/set_prefs" \
+            "{\"session_id\":\"$session\",\"tiers\":[$tiers]}" \
+            "Setting $name's preferences"
+    fi
+}
+
 echo -e "${YELLOW}Cleaning up...${NC}"
 rm -f backend/state.json 2>/dev/null
 echo "Old state removed"
@@ -37,28 +59,22 @@ echo
 # 1. REGISTER GROUPS
 echo -e "${GREEN}REGISTERING GROUPS${NC}"
 
-make_request POST "/group" \
-    '{"name":"g1","email":"g1@test.com","password":"test","preferences":["c1","c2","c3"]}' \
+register_group g1 "c1 c2 c3" \
     "Registering group g1"
 
-make_request POST "/group" \
-    '{"name":"g2","email":"g2@test.com","password":"test","preferences":["c2","c1","c3"]}' \
+register_group g2 "c2 c1 c3" \
     "Registering group g2"
 
-make_request POST "/group" \
-    '{"name":"g3","email":"g3@test.com","password":"test","preferences":["c3","c2","c1"]}' \
+register_group g3 "c3 c2 c1" \
     "Registering group g3"
 
-make_request POST "/group" \
-    '{"name":"g4","email":"g4@test.com","password":"test","preferences":["c1","c2"]}' \
+register_group g4 "c1 c2" \
     "Registering group g4"
 
-make_request POST "/group" \
-    '{"name":"g5","email":"g5@test.com","password":"test","preferences":["c3"]}' \
+register_group g5 "c3" \
     "Registering group g5 (only 1 preference)"
 
-make_request POST "/group" \
-    '{"name":"g6","email":"g6@test.com","password":"test","preferences":[]}' \
+register_group g6 "" \
     "Registering group g6 (no preferences)"
 
 echo
@@ -131,8 +147,7 @@ echo
 # 6. CREATE SOME UNMATCHABLE CASES (FOR TESTING)
 echo -e "${GREEN}CREATING SPECIAL TEST CASES${NC}"
 
-make_request POST "/group" \
-    '{"name":"g7","email":"g7@test.com","password":"test","preferences":["nonexistent1","nonexistent2"]}' \
+register_group g7 "nonexistent1 nonexistent2" \
     "Registering group g7 (non-existent company preferences)"
 
 make_request POST "/company" \
@@ -0,0 +1,267 @@
+use crate::models::{Group, AggregationRule, MemberRanking, MemberAgreement, AggregationReport};
+
+// Kemeny-Young przegląda wszystkie podzbiory kandydatów, więc tylko dla krótkich list
+pub const KEMENY_LIMIT: usize = 10;
+
+// Kandydaci w kolejności pierwszego wystąpienia na listach członków
+fn candidates(rankings: &[MemberRanking]) -> Vec<String> {
+    let mut all: Vec<String> = Vec::new();
+    for ranking in rankings {
+        for pref in &ranking.preferences {
+            if !all.contains(pref) {
+                all.push(pref.clone());
+            }
+        }
+    }
+    all
+}
+
+// Podwojona pozycja kandydata na liście członka; nieocenieni remisują na średniej pozostałych miejsc
+fn doubled_position(ranking: &[String], candidate: &str, count: usize) -> usize {
+    match ranking.iter().position(|p| p == candidate) {
+        Some(position) => 2 * position,
+        None => ranking.len() + count - 1,
+    }
+}
+
+// wins[a][b] = liczba członków, którzy wolą a od b
+fn pairwise_wins(rankings: &[MemberRanking], all: &[String]) -> Vec<Vec<usize>> {
+    let positions: Vec<Vec<usize>> = rankings.iter()
+        .map(|r| all.iter().map(|c| doubled_position(&r.preferences, c, all.len())).collect())
+        .collect();
+
+    let mut wins = vec![vec![0; all.len()]; all.len()];
+    for member in &positions {
+        for a in 0..all.len() {
+            for b in 0..all.len() {
+                if member[a] < member[b] {
+                    wins[a][b] += 1;
+                }
+            }
+        }
+    }
+    wins
+}
+
+// Kolejność według malejącego wyniku; równe wyniki dają remis (ten sam poziom)
+fn by_score(all: &[String], scores: &[i64]) -> (Vec<String>, Vec<usize>) {
+    let mut order: Vec<usize> = (0..all.len()).collect();
+    order.sort_by_key(|&c| std::cmp::Reverse(scores[c]));
+
+    let mut tiers = Vec::new();
+    for (i, &c) in order.iter().enumerate() {
+        let tie = i > 0 && scores[order[i - 1]] == scores[c];
+        tiers.push(if tie { tiers[i - 1] } else { i });
+    }
+    (order.into_iter().map(|c| all[c].clone()).collect(), tiers)
+}
+
+fn borda(rankings: &[MemberRanking], all: &[String]) -> (Vec<String>, Vec<usize>) {
+    let scores: Vec<i64> = all.iter()
+        .map(|c| rankings.iter()
+            .map(|r| (2 * (all.len() - 1) - doubled_position(&r.preferences, c, all.len())) as i64)
+            .sum())
+        .collect();
+    by_score(all, &scores)
+}
+
+// Wynik Copelanda: wygrane minus przegrane pojedynki większościowe
+fn copeland(rankings: &[MemberRanking], all: &[String]) -> (Vec<String>, Vec<usize>) {
+    let wins = pairwise_wins(rankings, all);
+    let scores: Vec<i64> = (0..all.len())
+        .map(|a| (0..all.len())
+            .map(|b| (wins[a][b] > wins[b][a]) as i64 - (wins[a][b] < wins[b][a]) as i64)
+            .sum())
+        .collect();
+    by_score(all, &scores)
+}
+
+// Ranking o najmniejszej łącznej liczbie par odwróconych względem list członków
+// (programowanie dynamiczne po zbiorach kandydatów już ustawionych na początku listy)
+fn kemeny_young(rankings: &[MemberRanking], all: &[String]) -> (Vec<String>, Vec<usize>) {
+    let n = all.len();
+    let wins = pairwise_wins(rankings, all);
+    let mut cost = vec![usize::MAX; 1 << n];
+    let mut last = vec![0; 1 << n];
+    cost[0] = 0;
+
+    for placed in 0..(1usize << n) {
+        if cost[placed] == usize::MAX {
+            continue;
+        }
+        for next in (0..n).filter(|&c| placed & (1 << c) == 0) {
+            // Każdy jeszcze nieustawiony kandydat trafi poniżej `next`
+            let against: usize = (0..n)
+                .filter(|&c| c != next && placed & (1 << c) == 0)
+                .map(|c| wins[c][next])
+                .sum();
+            let extended = placed | (1 << next);
+            if cost[placed] + against < cost[extended] {
+                cost[extended] = cost[placed] + against;
+                last[extended] = next;
+            }
+        }
+    }
+
+    let mut order = Vec::new();
+    let mut placed = (1usize << n) - 1;
+    while placed != 0 {
+        order.push(last[placed]);
+        placed &= !(1 << last[placed]);
+    }
+    order.reverse();
+    (order.into_iter().map(|c| all[c].clone()).collect(), (0..n).collect())
+}
+
+// Lista grupy (z poziomami) złożona z list członków; None, gdy lista jest za długa dla Kemeny-Younga
+pub fn aggregate(rule: AggregationRule, rankings: &[MemberRanking]) -> Option<(Vec<String>, Vec<usize>)> {
+    let all = candidates(rankings);
+    match rule {
+        AggregationRule::Borda => Some(borda(rankings, &all)),
+        AggregationRule::Copeland => Some(copeland(rankings, &all)),
+        AggregationRule::KemenyYoung if all.len() <= KEMENY_LIMIT => Some(kemeny_young(rankings, &all)),
+        AggregationRule::KemenyYoung => None,
+    }
+}
+
+// Zastępuje listę grupy agregatem list członków; false, gdy reguły nie da się zastosować
+pub fn apply(group: &mut Group) -> bool {
+    if group.member_rankings.is_empty() {
+        return true;
+    }
+    let Some((preferences, mut tiers)) = aggregate(group.aggregation, &group.member_rankings) else {
+        return false;
+    };
+    // Bez remisów zwykła ścisła lista
+    if tiers.iter().enumerate().all(|(i, &t)| i == t) {
+        tiers.clear();
+    }
+    group.preferences = preferences;
+    group.tiers = tiers;
+    true
+}
+
+// Odsetek par rozstrzygniętych przez członka, które lista grupy ustawia tak samo (remis liczy się za pół)
+pub fn agreement(member: &[String], preferences: &[String], tiers: &[usize]) -> f64 {
+    let tier_of = |c: &str| preferences.iter()
+        .position(|p| p == c)
+        .map(|i| if tiers.len() == preferences.len() { tiers[i] } else { i });
+    let count = preferences.len().max(member.len());
+
+    let mut agreed = 0.0;
+    let mut pairs = 0;
+    for a in preferences {
+        for b in preferences {
+            if doubled_position(member, a, count) >= doubled_position(member, b, count) {
+                continue;
+            }
+            pairs += 1;
+            agreed += match tier_of(a).cmp(&tier_of(b)) {
+                std::cmp::Ordering::Less => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Greater => 0.0,
+            };
+        }
+    }
+    if pairs == 0 { 1.0 } else { agreed / pairs as f64 }
+}
+
+pub fn aggregation_report(group: &Group) -> AggregationReport {
+    AggregationReport {
+        rule: group.aggregation,
+        preferences: group.preferences.clone(),
+        tiers: group.tiers.clone(),
+        members: group.member_rankings.iter()
+            .map(|r| MemberAgreement {
+                member: r.member.clone(),
+                preferences: r.preferences.clone(),
+                agreement: agreement(&r.preferences, &group.preferences, &group.tiers),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::seq::SliceRandom;
+    use rand_chacha::ChaCha8Rng;
+
+    fn ranking(member: &str, preferences: &[&str]) -> MemberRanking {
+        MemberRanking {
+            member: member.into(),
+            preferences: preferences.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    // Łączna liczba par, które lista ustawia odwrotnie niż członkowie
+    fn disagreements(rankings: &[MemberRanking], order: &[String]) -> usize {
+        let wins = pairwise_wins(rankings, order);
+        (0..order.len())
+            .flat_map(|i| (i + 1..order.len()).map(move |j| (i, j)))
+            .map(|(i, j)| wins[j][i])
+            .sum()
+    }
+
+    fn permutations(items: &[String]) -> Vec<Vec<String>> {
+        if items.is_empty() {
+            return vec![vec![]];
+        }
+        (0..items.len())
+            .flat_map(|i| {
+                let mut rest = items.to_vec();
+                let first = rest.remove(i);
+                permutations(&rest).into_iter().map(move |mut p| {
+                    p.insert(0, first.clone());
+                    p
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn kemeny_young_minimizes_disagreements() {
+        let companies: Vec<String> = ["a", "b", "c", "d", "e"].iter().map(|c| c.to_string()).collect();
+        for seed in 0..100 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let rankings: Vec<MemberRanking> = (0..rng.gen_range(1..=4))
+                .map(|m| {
+                    let mut prefs = companies.clone();
+                    prefs.shuffle(&mut rng);
+                    prefs.truncate(rng.gen_range(1..=companies.len()));
+                    MemberRanking { member: format!("m{}", m), preferences: prefs }
+                })
+                .collect();
+
+            let all = candidates(&rankings);
+            let best = permutations(&all).iter().map(|p| disagreements(&rankings, p)).min().unwrap();
+            let (order, _) = aggregate(AggregationRule::KemenyYoung, &rankings).unwrap();
+            assert_eq!(disagreements(&rankings, &order), best, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn rules_on_a_split_group() {
+        let rankings = [
+            ranking("ala", &["a", "b", "c"]),
+            ranking("ola", &["a", "b", "c"]),
+            ranking("ela", &["b", "c", "a"]),
+        ];
+
+        // Borda: a = 4 + 4 + 0, b = 2 + 2 + 4, c = 0 + 0 + 2 (w podwojonych punktach)
+        let (borda, tiers) = aggregate(AggregationRule::Borda, &rankings).unwrap();
+        assert_eq!(borda, ["a", "b", "c"]);
+        assert_eq!(tiers, [0, 0, 2]);
+
+        let (copeland, tiers) = aggregate(AggregationRule::Copeland, &rankings).unwrap();
+        assert_eq!(copeland, ["a", "b", "c"]);
+        assert_eq!(tiers, [0, 1, 2]);
+
+        assert_eq!(agreement(&rankings[0].preferences, &copeland, &tiers), 1.0);
+        assert!((agreement(&rankings[2].preferences, &copeland, &tiers) - 1.0 / 3.0).abs() < 1e-9);
+
+        let long: Vec<&str> = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"].to_vec();
+        assert!(aggregate(AggregationRule::KemenyYoung, &[ranking("ala", &long)]).is_none());
+    }
+}
@@ -0,0 +1,423 @@
+use crate::{
+    models::{Group, Company, MatchParams, MatchResponse, MatchRun, RunSummary, ListSnapshot, InputSnapshot, RunDiff, GroupMove, PreferenceChange},
+    state::AppState,
+    matching::shortlisted_lists,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+pub fn group_snapshot(g: &Group) -> ListSnapshot {
+    ListSnapshot {
+        name: g.name.clone(),
+        preferences: g.preferences.clone(),
+        tiers: g.tiers.clone(),
+        priority: g.priority,
+        track: g.track.clone(),
+        skills: g.skills.clone(),
+        ..Default::default()
+    }
+}
+
+pub fn company_snapshot(c: &Company) -> ListSnapshot {
+    ListSnapshot {
+        name: c.name.clone(),
+        preferences: c.preferences.clone(),
+        tiers: c.tiers.clone(),
+        min_groups: c.min_groups,
+        reserved: c.reserved.clone(),
+        skills: c.required_skills.clone(),
+        ..Default::default()
+    }
+}
+
+pub fn snapshot(s: &AppState) -> InputSnapshot {
+    InputSnapshot {
+        groups: s.groups.iter().map(group_snapshot).collect(),
+        companies: s.companies.iter().map(company_snapshot).collect(),
+        projects: s.projects.clone(),
+        constraints: s.constraints.clone(),
+        applications: s.applications.clone(),
+        applications_required: s.applications_required,
+    }
+}
+
+// Listy grup i firm odtworzone z zapisu, w takiej postaci, w jakiej trafiły do dopasowania
+pub fn restore(input: &InputSnapshot) -> (Vec<Group>, Vec<Company>) {
+    let groups: Vec<Group> = input.groups.iter()
+        .map(|g| Group {
+            name: g.name.clone(),
+            preferences: g.preferences.clone(),
+            tiers: g.tiers.clone(),
+            priority: g.priority,
+            track: g.track.clone(),
+            skills: g.skills.clone(),
+            ..Default::default()
+        })
+        .collect();
+    let companies: Vec<Company> = input.companies.iter()
+        .map(|c| Company {
+            name: c.name.clone(),
+            preferences: c.preferences.clone(),
+            tiers: c.tiers.clone(),
+            min_groups: c.min_groups,
+            reserved: c.reserved.clone(),
+            required_skills: c.skills.clone(),
+            ..Default::default()
+        })
+        .collect();
+
+    if input.applications_required {
+        shortlisted_lists(&groups, &companies, &input.projects, &input.applications)
+    } else {
+        (groups, companies)
+    }
+}
+
+// FNV-1a po JSON-ie danych wejściowych; stabilny między wersjami kompilatora
+pub fn input_hash(input: &InputSnapshot) -> String {
+    let json = serde_json::to_string(input).unwrap_or_default();
+    let hash = json.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+// Najwięcej zapisanych przebiegów; najstarsze (poza opublikowanym) są usuwane
+const MAX_RUNS: usize = 100;
+
+// Zapisuje przebieg w stanie i wpisuje jego id do odpowiedzi. Powtórzenie ostatniego przebiegu
+// (te same parametry z seedem i te same dane) dostaje jego id i nie jest zapisywane; zwraca, czy coś dodano
+pub fn record_run(s: &mut AppState, params: &MatchParams, response: &mut MatchResponse) -> bool {
+    let input = snapshot(s);
+    let hash = input_hash(&input);
+    let params = MatchParams { seed: response.seed, ..params.clone() };
+
+    let same_params = |r: &MatchRun| serde_json::to_value(&r.params).ok() == serde_json::to_value(&params).ok();
+    if let Some(last) = s.runs.last().filter(|r| r.input_hash == hash && same_params(r)) {
+        response.run_id = Some(last.id.clone());
+        return false;
+    }
+
+    let id = Uuid::new_v4().to_string();
+    s.runs.push(MatchRun {
+        id: id.clone(),
+        created_at: now(),
+        algorithm: response.algorithm,
+        params,
+        seed: response.seed,
+        input_hash: hash,
+        input,
+        results: response.results.clone(),
+        unmatched: response.unmatched.clone(),
+    });
+    response.run_id = Some(id);
+
+    while s.runs.len() > MAX_RUNS {
+        let published = s.published.as_ref().map(|p| p.run_id.clone());
+        let Some(oldest) = s.runs.iter().position(|r| Some(&r.id) != published.as_ref()) else {
+            break;
+        };
+        s.runs.remove(oldest);
+    }
+    true
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+pub fn published_run(s: &AppState) -> Option<&MatchRun> {
+    let publication = s.published.as_ref()?;
+    s.runs.iter().find(|r| r.id == publication.run_id)
+}
+
+// Opublikowany przebieg w postaci odpowiedzi /match; wynik pochodzi z zapisu, nie z obecnych list
+pub fn published_response(s: &AppState) -> Option<MatchResponse> {
+    let run = published_run(s)?;
+
+    Some(MatchResponse {
+        algorithm: run.algorithm,
+        proposer: run.params.proposer,
+        ties: run.params.ties,
+        seed: run.seed,
+        run_id: Some(run.id.clone()),
+        unmatched_policy: run.params.unmatched,
+        unmatched: run.unmatched.clone(),
+        tie_report: None,
+        comparison: None,
+        results: run.results.clone(),
+        published: true,
+        rematch: None,
+        constraints: None,
+        quotas: None,
+        reservations: None,
+    })
+}
+
+pub fn summary(run: &MatchRun) -> RunSummary {
+    RunSummary {
+        id: run.id.clone(),
+        created_at: run.created_at,
+        algorithm: run.algorithm,
+        params: run.params.clone(),
+        seed: run.seed,
+        input_hash: run.input_hash.clone(),
+        matched: run.results.len(),
+        unmatched: run.unmatched.len(),
+    }
+}
+
+// Nazwy pól, którymi różnią się dwie wersje listy
+fn changed_fields(old: &ListSnapshot, new: &ListSnapshot) -> Vec<String> {
+    let (old, new) = (serde_json::to_value(old).unwrap_or_default(), serde_json::to_value(new).unwrap_or_default());
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return vec![];
+    };
+    old.iter()
+        .filter(|(key, value)| new.get(*key) != Some(value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+pub fn list_changes(side: &str, before: &[ListSnapshot], after: &[ListSnapshot]) -> Vec<PreferenceChange> {
+    let mut names: Vec<&String> = before.iter().chain(after).map(|l| &l.name).collect();
+    names.sort();
+    names.dedup();
+
+    names.into_iter()
+        .filter_map(|name| {
+            let old = before.iter().find(|l| &l.name == name);
+            let new = after.iter().find(|l| &l.name == name);
+            (old != new).then(|| PreferenceChange {
+                side: side.into(),
+                name: name.clone(),
+                before: old.map(|l| l.preferences.clone()),
+                after: new.map(|l| l.preferences.clone()),
+                fields: old.zip(new).map(|(o, n)| changed_fields(o, n)).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+// Opis zmiany listy, np. "company 'c1' changed its preferences, tiers"
+pub fn describe_change(change: &PreferenceChange) -> String {
+    match (&change.before, &change.after) {
+        (None, _) => format!("{} '{}' was added", change.side, change.name),
+        (_, None) => format!("{} '{}' was removed", change.side, change.name),
+        _ => {
+            let fields: Vec<String> = change.fields.iter()
+                .map(|f| match (change.side.as_str(), f.as_str()) {
+                    ("company", "skills") => "required skills".into(),
+                    _ => f.replace('_', " "),
+                })
+                .collect();
+            format!("{} '{}' changed its {}", change.side, change.name, fields.join(", "))
+        }
+    }
+}
+
+fn parameter_changes(from: &MatchRun, to: &MatchRun) -> Vec<String> {
+    let describe = |run: &MatchRun| serde_json::to_value(&run.params).unwrap_or_default();
+    let (old, new) = (describe(from), describe(to));
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return vec![];
+    };
+
+    let mut changes: Vec<String> = old.iter()
+        .filter(|(key, value)| new.get(*key) != Some(value))
+        .map(|(key, value)| format!("{}: {} -> {}", key, value, new.get(key).cloned().unwrap_or_default()))
+        .collect();
+
+    let constraints = |run: &MatchRun| serde_json::to_value(&run.input.constraints).unwrap_or_default();
+    if constraints(from) != constraints(to) {
+        changes.push("pinned or forbidden pairs changed".into());
+    }
+    changes
+}
+
+fn project_of(run: &MatchRun, group: &str) -> Option<(String, String)> {
+    run.results.iter()
+        .find(|m| m.group == group)
+        .map(|m| (m.project.clone(), m.company.clone()))
+}
+
+// Grupy, które zmieniły projekt między przebiegami, z możliwymi przyczynami
+pub fn diff_runs(from: &MatchRun, to: &MatchRun) -> RunDiff {
+    let mut preference_changes = list_changes("group", &from.input.groups, &to.input.groups);
+    preference_changes.extend(list_changes("company", &from.input.companies, &to.input.companies));
+    let parameter_changes = parameter_changes(from, to);
+
+    let changed = |side: &str, name: &str| preference_changes.iter().find(|c| c.side == side && c.name == name);
+    let capacity = |run: &MatchRun, project: &str| run.input.projects.iter()
+        .find(|p| p.id == project)
+        .map(|p| p.capacity);
+
+    let mut groups: Vec<&String> = from.results.iter().chain(&to.results).map(|m| &m.group).collect();
+    groups.sort();
+    groups.dedup();
+
+    let moved = groups.into_iter()
+        .filter_map(|group| {
+            let before = project_of(from, group);
+            let after = project_of(to, group);
+            if before.as_ref().map(|b| &b.0) == after.as_ref().map(|a| &a.0) {
+                return None;
+            }
+
+            let mut reasons = Vec::new();
+            if let Some(change) = changed("group", group) {
+                reasons.push(describe_change(change));
+            }
+            let mut companies: Vec<&String> = before.iter().chain(after.iter()).map(|(_, c)| c).collect();
+            companies.dedup();
+            for company in companies {
+                if let Some(change) = changed("company", company) {
+                    reasons.push(describe_change(change));
+                }
+            }
+            for (project, _) in before.iter().chain(after.iter()) {
+                let (old, new) = (capacity(from, project), capacity(to, project));
+                if old != new {
+                    reasons.push(format!("project '{}' capacity changed: {:?} -> {:?}", project, old, new));
+                }
+            }
+            if reasons.is_empty() {
+                let others: Vec<String> = preference_changes.iter()
+                    .map(|c| format!("{} '{}'", c.side, c.name))
+                    .collect();
+                if !others.is_empty() {
+                    reasons.push(format!("knock-on effect of changed preferences: {}", others.join(", ")));
+                }
+            }
+            reasons.extend(parameter_changes.iter().map(|c| format!("parameter changed ({})", c)));
+            if reasons.is_empty() {
+                reasons.push("same input and parameters; results differ".into());
+            }
+
+            Some(GroupMove {
+                group: group.clone(),
+                from_project: before.map(|(p, _)| p),
+                to_project: after.map(|(p, _)| p),
+                reasons,
+            })
+        })
+        .collect();
+
+    RunDiff {
+        from: from.id.clone(),
+        to: to.id.clone(),
+        same_input: from.input_hash == to.input_hash,
+        parameter_changes,
+        preference_changes,
+        moved,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{run_matching, testing::project};
+    use crate::models::{MatchResult, Application, ApplicationStatus, Publication, TieBreak};
+
+    fn run(id: &str, company_prefs: &[&str], results: &[(&str, &str)]) -> MatchRun {
+        let list = |name: &str, prefs: &[&str]| ListSnapshot {
+            name: name.into(),
+            preferences: prefs.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        };
+        let input = InputSnapshot {
+            groups: vec![list("g1", &["c1", "c2"]), list("g2", &["c1", "c2"])],
+            companies: vec![list("c1", company_prefs), list("c2", &["g1", "g2"])],
+            projects: vec![],
+            ..Default::default()
+        };
+        MatchRun {
+            id: id.into(),
+            created_at: 0,
+            algorithm: Default::default(),
+            params: Default::default(),
+            seed: None,
+            input_hash: input_hash(&input),
+            input,
+            results: results.iter()
+                .map(|&(group, company)| MatchResult {
+                    group: group.into(),
+                    company: company.into(),
+                    project: company.into(),
+                    ..Default::default()
+                })
+                .collect(),
+            unmatched: vec![],
+        }
+    }
+
+    #[test]
+    fn diff_blames_the_company_whose_list_changed() {
+        let before = run("a", &["g1", "g2"], &[("g1", "c1"), ("g2", "c2")]);
+        let after = run("b", &["g2", "g1"], &[("g1", "c2"), ("g2", "c1")]);
+        let diff = diff_runs(&before, &after);
+
+        assert!(!diff.same_input);
+        assert_eq!(diff.preference_changes.len(), 1);
+        assert_eq!(diff.moved.len(), 2);
+        assert!(diff.moved.iter().all(|m| m.reasons == vec!["company 'c1' changed its preferences".to_string()]));
+
+        let again = diff_runs(&before, &before);
+        assert!(again.same_input && again.moved.is_empty());
+
+        // Zmiana poziomów bez zmiany kolejności jest nazwana wprost
+        let mut tied = run("c", &["g1", "g2"], &[("g1", "c2"), ("g2", "c1")]);
+        tied.input.groups[0].tiers = vec![0, 0];
+        tied.input.groups[0].skills = vec!["rust".into()];
+        let diff = diff_runs(&before, &tied);
+        assert_eq!(diff.preference_changes[0].fields, ["skills", "tiers"]);
+        let g1 = diff.moved.iter().find(|m| m.group == "g1").unwrap();
+        assert_eq!(g1.reasons, ["group 'g1' changed its skills, tiers"]);
+    }
+
+    #[test]
+    fn restore_keeps_the_recorded_shortlist() {
+        let mut input = run("a", &["g1", "g2"], &[]).input;
+        input.projects = vec![project("c1", "c1", 1), project("c2", "c2", 1)];
+        input.applications = vec![Application {
+            group: "g1".into(),
+            company: "c2".into(),
+            project: None,
+            motivation: String::new(),
+            status: ApplicationStatus::Shortlisted,
+        }];
+
+        let (groups, companies) = restore(&input);
+        assert_eq!(groups[0].preferences, ["c1", "c2"]);
+        assert_eq!(companies[0].preferences, ["g1", "g2"]);
+
+        input.applications_required = true;
+        let (groups, companies) = restore(&input);
+        assert_eq!(groups[0].preferences, ["c2"]);
+        assert!(groups[1].preferences.is_empty());
+        assert!(companies[0].preferences.is_empty());
+        assert_eq!(companies[1].preferences, ["g1"]);
+    }
+
+    #[test]
+    fn repeated_runs_are_not_recorded_and_history_is_capped() {
+        let mut s = AppState::new();
+        let record = |s: &mut AppState, seed: u64| {
+            let params = MatchParams { ties: TieBreak::Random, seed: Some(seed), ..Default::default() };
+            let mut response = run_matching(&[], &[], &[], &params, None, &Default::default(), None).unwrap();
+            let added = record_run(s, &params, &mut response);
+            (added, response.run_id.unwrap())
+        };
+
+        let (added, first) = record(&mut s, 0);
+        assert!(added);
+        assert_eq!(record(&mut s, 0), (false, first.clone()));
+        assert_eq!(s.runs.len(), 1);
+
+        s.published = Some(Publication { run_id: first.clone(), published_at: 0 });
+        for seed in 1..=MAX_RUNS as u64 + 5 {
+            assert!(record(&mut s, seed).0);
+        }
+        assert_eq!(s.runs.len(), MAX_RUNS);
+        assert_eq!(s.runs[0].id, first);
+        assert_eq!(s.runs[1].params.seed, Some(7));
+    }
+}
@@ -1,6 +1,11 @@
 mod state;
 mod models;
 mod matching;
+mod history;
+mod aggregation;
+mod teams;
+mod rubric;
+mod schedule;
 mod routes;
 
 use tower_http::cors::{CorsLayer, Any};
@@ -34,15 +39,74 @@ async fn main() {
     let app = Router::new()
         .route("/group", post(routes::add_group))
         .route("/company", post(routes::add_company))
+        .route("/student", post(routes::add_student))
         .route("/match", get(routes::match_groups))
+        .route("/match/algorithms", get(routes::list_algorithms))
+        .route("/match/runs", get(routes::list_runs))
+        .route("/match/run", get(routes::get_run))
+        .route("/match/diff", get(routes::diff_match_runs))
+        .route("/match/replay", get(routes::replay_match))
+        .route("/match/verify", get(routes::verify_match))
+        .route("/admin/match/lattice", get(routes::admin_match_lattice))
+        .route("/admin/match/publish", post(routes::admin_publish_run))
+        .route("/admin/match/unpublish", post(routes::admin_unpublish))
+        .route("/admin/constraints", get(routes::admin_constraints))
+        .route("/admin/constraints/pin", post(routes::admin_pin_pair))
+        .route("/admin/constraints/forbid", post(routes::admin_forbid_pair))
+        .route("/admin/constraints/remove", post(routes::admin_remove_constraint))
+        .route("/admin/group/priority", post(routes::admin_set_priority))
+        .route("/admin/quota", post(routes::admin_set_quota))
+        .route("/admin/group/track", post(routes::admin_set_track))
+        .route("/admin/applications", post(routes::admin_set_application_phase))
+        .route("/admin/group/size", post(routes::admin_set_group_size))
+        .route("/admin/teams", get(routes::admin_team_proposal))
+        .route("/admin/teams/propose", post(routes::admin_propose_teams))
+        .route("/admin/teams/accept", post(routes::admin_accept_teams))
         .route("/login/group", post(routes::login_group))
         .route("/login/company", post(routes::login_company))
         .route("/group/me", get(routes::group_me))
         .route("/company/me", get(routes::company_me))
         .route("/company/list", get(routes::list_companies))
         .route("/group/list", get(routes::list_groups))
+        .route("/student/list", get(routes::list_students))
+        .route("/student/me", get(routes::student_me))
+        .route("/student/update", post(routes::student_update))
+        .route("/student/group", post(routes::student_create_group))
+        .route("/student/invitations", get(routes::student_invitations))
+        .route("/student/accept", post(routes::student_accept_invitation))
+        .route("/student/decline", post(routes::student_decline_invitation))
+        .route("/student/leave", post(routes::student_leave_group))
+        .route("/login/student", post(routes::login_student))
         .route("/group/add_pref", post(routes::group_add_pref))
         .route("/company/add_pref", post(routes::company_add_pref))
+        .route("/group/set_prefs", post(routes::group_set_prefs))
+        .route("/company/set_prefs", post(routes::company_set_prefs))
+        .route("/company/add_project", post(routes::company_add_project))
+        .route("/company/reserve", post(routes::company_reserve_seats))
+        .route("/company/skills", post(routes::company_set_skills))
+        .route("/company/eligibility", get(routes::company_eligibility))
+        .route("/group/apply", post(routes::group_apply))
+        .route("/group/withdraw", post(routes::group_withdraw_application))
+        .route("/group/applications", get(routes::group_applications))
+        .route("/company/applications", get(routes::company_applications))
+        .route("/company/rubric", get(routes::company_rubric).post(routes::company_set_rubric))
+        .route("/company/score", post(routes::company_submit_review))
+        .route("/company/slots", post(routes::company_add_slot))
+        .route("/company/slots/remove", post(routes::company_remove_slot))
+        .route("/company/schedule", get(routes::company_schedule))
+        .route("/company/schedule.ics", get(routes::company_schedule_ics))
+        .route("/interview/slots", get(routes::list_open_slots))
+        .route("/group/book", post(routes::group_book_slot))
+        .route("/group/cancel", post(routes::group_cancel_slot))
+        .route("/group/schedule", get(routes::group_schedule))
+        .route("/group/schedule.ics", get(routes::group_schedule_ics))
+        .route("/student/availability", post(routes::student_set_availability))
+        .route("/company/review", post(routes::company_review_application))
+        .route("/group/invite", post(routes::group_invite))
+        .route("/group/skills", post(routes::group_set_skills))
+        .route("/group/member_ranking", post(routes::group_submit_ranking))
+        .route("/group/aggregation", get(routes::group_aggregation).post(routes::group_set_aggregation))
+        .route("/project/list", get(routes::list_projects))
         .layer(cors)
         .with_state(state);
 
@@ -1,151 +1,417 @@
-use crate::models::{Group, Company, MatchResult};
+use crate::models::{Group, Company, Project, MatchResult, Proposer};
 use std::collections::{HashMap, VecDeque};
 
-pub fn stable_matching(groups: &[Group], companies: &[Company]) -> Vec<MatchResult> {
-    if groups.is_empty() || companies.is_empty() {
-        println!("No groups or companies to match");
-        return vec![];
+mod assignment;
+mod constraints;
+mod eligibility;
+mod fallback;
+mod lattice;
+mod optimal;
+mod quotas;
+mod reserves;
+mod registry;
+mod serial;
+mod shortlist;
+mod stability;
+mod ties;
+mod trading;
+#[cfg(test)]
+pub(crate) mod testing;
+
+pub use assignment::{rank_maximal_matching, min_cost_matching, rank_profile};
+pub use constraints::{is_forbidden, constrained_lattice, verify_constrained};
+pub use eligibility::{eligible_lists, eligibility_report};
+pub use fallback::place_unmatched;
+pub use lattice::stable_lattice;
+pub use optimal::{egalitarian_matching, minimum_regret_matching, minimal_disruption_matching, disruption_report};
+pub use quotas::lower_quota_matching;
+pub use reserves::track_reserve_matching;
+pub use registry::{algorithm_catalog, run_matching};
+pub use serial::random_serial_dictatorship;
+pub use shortlist::shortlisted_lists;
+pub use stability::verify_stability;
+pub use trading::top_trading_cycles;
+pub use ties::{break_ties, lottery_ties, super_stable_matching, strongly_stable_matching};
+
+// Firmy bez własnych projektów dostają jeden domyślny projekt o pojemności 1
+pub fn company_projects(companies: &[Company], projects: &[Project]) -> Vec<Project> {
+    let mut result = Vec::new();
+
+    for c in companies {
+        let own: Vec<&Project> = projects.iter()
+            .filter(|p| p.company == c.name)
+            .collect();
+
+        if own.is_empty() {
+            result.push(Project {
+                id: c.name.clone(),
+                company: c.name.clone(),
+                title: c.name.clone(),
+                description: String::new(),
+                capacity: 1,
+                min_capacity: 0,
+                required_skills: vec![],
+            });
+        } else {
+            result.extend(own.into_iter().cloned());
+        }
+    }
+    result
+}
+
+// Poziomy pozycji listy; bez (poprawnych) poziomów lista jest ścisła
+fn preference_tiers(preferences: &[String], tiers: &[usize]) -> Vec<usize> {
+    if tiers.len() == preferences.len() {
+        tiers.to_vec()
+    } else {
+        (0..preferences.len()).collect()
+    }
+}
+
+// Numeruje poziomy kolejno od zera, zachowując remisy
+fn dense_tiers(tiers: &[usize]) -> Vec<usize> {
+    let mut distinct = tiers.to_vec();
+    distinct.sort();
+    distinct.dedup();
+    tiers.iter()
+        .map(|t| distinct.binary_search(t).unwrap())
+        .collect()
+}
+
+// Preferencja grupy to id projektu albo nazwa firmy (wtedy wszystkie jej projekty po kolei,
+// a przy liście z remisami wszystkie na poziomie tej pozycji)
+fn resolve_preferences(group: &Group, projects: &[Project]) -> (Vec<usize>, Vec<usize>) {
+    let strict = group.tiers.len() != group.preferences.len();
+    let mut resolved = Vec::new();
+    let mut tiers = Vec::new();
+
+    for (position, pref) in group.preferences.iter().enumerate() {
+        let found: Vec<usize> = match projects.iter().position(|p| &p.id == pref) {
+            Some(p_idx) => vec![p_idx],
+            None => projects.iter()
+                .enumerate()
+                .filter(|(_, p)| &p.company == pref)
+                .map(|(p_idx, _)| p_idx)
+                .collect(),
+        };
+
+        for p_idx in found {
+            if !resolved.contains(&p_idx) {
+                tiers.push(if strict { resolved.len() } else { group.tiers[position] });
+                resolved.push(p_idx);
+            }
+        }
+    }
+    (resolved, dense_tiers(&tiers))
+}
+
+struct Market<'a> {
+    groups: &'a [Group],
+    companies: &'a [Company],
+    projects: Vec<Project>,
+    project_company: Vec<usize>,
+    group_prefs: Vec<Vec<usize>>,
+    company_prefs: Vec<Vec<usize>>,
+    // Poziomy (od 0) równoległe do group_prefs / company_prefs
+    group_tiers: Vec<Vec<usize>>,
+    company_tiers: Vec<Vec<usize>>,
+}
+
+impl<'a> Market<'a> {
+    fn new(groups: &'a [Group], companies: &'a [Company], projects: &[Project]) -> Self {
+        let company_idx: HashMap<&str, usize> = companies.iter()
+            .enumerate()
+            .map(|(i, c)| (c.name.as_str(), i))
+            .collect();
+
+        // Projekty firmy, której już nie ma (usuniętej albo po ręcznej edycji state.json), są pomijane
+        let (projects, project_company): (Vec<Project>, Vec<usize>) = company_projects(companies, projects)
+            .into_iter()
+            .filter_map(|p| company_idx.get(p.company.as_str()).map(|&c_idx| (p, c_idx)))
+            .unzip();
+
+        let group_idx: HashMap<&str, usize> = groups.iter()
+            .enumerate()
+            .map(|(i, g)| (g.name.as_str(), i))
+            .collect();
+
+        let (group_prefs, group_tiers) = groups.iter()
+            .map(|g| resolve_preferences(g, &projects))
+            .unzip();
+
+        let (company_prefs, company_tiers) = companies.iter()
+            .map(|c| {
+                let (prefs, tiers): (Vec<usize>, Vec<usize>) = c.preferences.iter()
+                    .zip(preference_tiers(&c.preferences, &c.tiers))
+                    .filter_map(|(name, tier)| group_idx.get(name.as_str()).map(|&g_idx| (g_idx, tier)))
+                    .unzip();
+                (prefs, dense_tiers(&tiers))
+            })
+            .unzip();
+
+        Market {
+            groups,
+            companies,
+            projects,
+            project_company,
+            group_prefs,
+            company_prefs,
+            group_tiers,
+            company_tiers,
+        }
+    }
+
+    fn group_rank(&self, g_idx: usize, p_idx: usize) -> Option<usize> {
+        self.group_prefs[g_idx].iter().position(|&p| p == p_idx)
+    }
+
+    fn company_rank(&self, p_idx: usize, g_idx: usize) -> Option<usize> {
+        self.company_prefs[self.project_company[p_idx]].iter().position(|&g| g == g_idx)
+    }
+
+    fn group_tier(&self, g_idx: usize, p_idx: usize) -> Option<usize> {
+        self.group_rank(g_idx, p_idx).map(|rank| self.group_tiers[g_idx][rank])
+    }
+
+    fn company_tier(&self, p_idx: usize, g_idx: usize) -> Option<usize> {
+        self.company_rank(p_idx, g_idx).map(|rank| self.company_tiers[self.project_company[p_idx]][rank])
+    }
+
+    fn results(&self, assignment: &[Option<usize>]) -> Vec<MatchResult> {
+        let mut results: Vec<MatchResult> = assignment.iter()
+            .enumerate()
+            .filter_map(|(g_idx, p_idx)| p_idx.map(|p_idx| {
+                let project = &self.projects[p_idx];
+                MatchResult {
+                    group: self.groups[g_idx].name.clone(),
+                    company: self.companies[self.project_company[p_idx]].name.clone(),
+                    project: project.id.clone(),
+                    project_title: project.title.clone(),
+                    ..Default::default()
+                }
+            }))
+            .collect();
+
+        results.sort_by(|a, b| a.group.cmp(&b.group));
+        self.annotate(&mut results);
+        results
+    }
+
+    // Uzupełnia pozycje obu stron, akceptowalność i wyjaśnienia względem całego skojarzenia
+    fn annotate(&self, results: &mut [MatchResult]) {
+        let located: Vec<Option<(usize, usize)>> = results.iter()
+            .map(|m| Some((
+                self.groups.iter().position(|g| g.name == m.group)?,
+                self.projects.iter().position(|p| p.id == m.project)?,
+            )))
+            .collect();
+
+        let mut holders: Vec<Vec<usize>> = vec![Vec::new(); self.projects.len()];
+        for &(g_idx, p_idx) in located.iter().flatten() {
+            holders[p_idx].push(g_idx);
+        }
+
+        for (m, location) in results.iter_mut().zip(located) {
+            let Some((g_idx, p_idx)) = location else {
+                continue;
+            };
+            m.group_rank = self.group_tier(g_idx, p_idx).map(|t| t + 1);
+            m.company_rank = self.company_tier(p_idx, g_idx).map(|t| t + 1);
+            m.mutually_acceptable = m.group_rank.is_some() && m.company_rank.is_some();
+            m.explanations = self.explain(g_idx, p_idx, &holders);
+        }
     }
 
-    let _group_idx: HashMap<String, usize> = groups.iter()
-        .enumerate()
-        .map(|(i, g)| (g.name.clone(), i))
-        .collect();
-
-    let company_idx: HashMap<String, usize> = companies.iter()
-        .enumerate()
-        .map(|(i, c)| (c.name.clone(), i))
-        .collect();
-
-    // Normalny Gale-Shapley z preferencjami
-    let mut free_groups: VecDeque<usize> = (0..groups.len()).collect();
-    let mut next_proposal: Vec<usize> = vec![0; groups.len()];
-    let mut company_partner: Vec<Option<usize>> = vec![None; companies.len()];
-    let mut company_score: Vec<HashMap<String, i32>> = vec![HashMap::new(); companies.len()];
-    
-    for (i, c) in companies.iter().enumerate() {
-        for (position, group_name) in c.preferences.iter().enumerate() {
-            let base_score = (position as i32 + 1) * 10;
-            let mut extra_points = 0;
-
-            if position < 3 {
-                extra_points -= 5;
+    // Po jednym zdaniu dla każdego projektu, który grupa postawiła wyżej niż swój przydział
+    fn explain(&self, g_idx: usize, p_idx: usize, holders: &[Vec<usize>]) -> Vec<String> {
+        let group = &self.groups[g_idx].name;
+        let current_tier = self.group_tier(g_idx, p_idx);
+        let mut explanations = Vec::new();
+
+        for (&q_idx, &tier) in self.group_prefs[g_idx].iter().zip(&self.group_tiers[g_idx]) {
+            if current_tier.is_some_and(|current| tier >= current) {
+                continue;
+            }
+            let project = &self.projects[q_idx];
+            let company = &project.company;
+
+            let missing = eligibility::missing_skills(&self.groups[g_idx], &self.companies[self.project_company[q_idx]], project);
+            if !missing.is_empty() {
+                explanations.push(format!(
+                    "#{} '{}': '{}' lacks skills required by '{}': {}",
+                    tier + 1, project.title, group, company, missing.join(", ")
+                ));
+                continue;
             }
-            if c.preferences.len() <= 2 {
-                extra_points -= 3;
+
+            let Some(c_tier) = self.company_tier(q_idx, g_idx) else {
+                explanations.push(format!(
+                    "#{} '{}': company '{}' did not rank '{}'",
+                    tier + 1, project.title, company, group
+                ));
+                continue;
+            };
+
+            let held = &holders[q_idx];
+            if held.len() < project.capacity {
+                explanations.push(format!(
+                    "#{} '{}': a seat was still free ({}/{}) but the algorithm did not use it",
+                    tier + 1, project.title, held.len(), project.capacity
+                ));
+                continue;
             }
-            
-            let final_score = base_score + extra_points;
-            company_score[i].insert(group_name.clone(), final_score);
+
+            let describe = |h_idx: usize| match self.company_tier(q_idx, h_idx) {
+                Some(h_tier) => format!("'{}' (#{})", self.groups[h_idx].name, h_tier + 1),
+                None => format!("'{}' (unranked)", self.groups[h_idx].name),
+            };
+            let held_names = held.iter().map(|&h_idx| describe(h_idx)).collect::<Vec<_>>().join(", ");
+            let all_better = held.iter().all(|&h_idx| self.company_tier(q_idx, h_idx).is_some_and(|h_tier| h_tier < c_tier));
+
+            explanations.push(if all_better {
+                format!(
+                    "#{} '{}': full; company '{}' held {} ranked above '{}' (#{})",
+                    tier + 1, project.title, company, held_names, group, c_tier + 1
+                )
+            } else {
+                format!(
+                    "#{} '{}': full with {}, not all of whom company '{}' ranks above '{}' (#{})",
+                    tier + 1, project.title, held_names, company, group, c_tier + 1
+                )
+            });
         }
+        explanations
     }
+}
+
+// Pozycje, akceptowalność i wyjaśnienia względem podanych (zwykle oryginalnych) list
+pub fn annotate_results(groups: &[Group], companies: &[Company], projects: &[Project], results: &mut [MatchResult]) {
+    Market::new(groups, companies, projects).annotate(results);
+}
+
+// Gale-Shapley w wersji szpitale/rezydenci: projekt trzyma do `capacity` najlepszych grup
+fn group_proposing(market: &Market) -> Vec<Option<usize>> {
+    let mut free_groups: VecDeque<usize> = (0..market.groups.len()).collect();
+    let mut next_proposal: Vec<usize> = vec![0; market.groups.len()];
+    let mut held: Vec<Vec<usize>> = vec![Vec::new(); market.projects.len()];
 
-    // Normalny Gale-Shapley
-    let mut matched_groups = vec![false; groups.len()];
-    
     while let Some(g_idx) = free_groups.pop_front() {
-        let group = &groups[g_idx];
-        if next_proposal[g_idx] >= group.preferences.len() {
+        let Some(&p_idx) = market.group_prefs[g_idx].get(next_proposal[g_idx]) else {
             continue;
-        }
-
-        let company_name = &group.preferences[next_proposal[g_idx]];
+        };
         next_proposal[g_idx] += 1;
 
-        let Some(&c_idx) = company_idx.get(company_name) else {
+        if market.company_rank(p_idx, g_idx).is_none() {
             free_groups.push_back(g_idx);
             continue;
-        };
+        }
 
-        match company_partner[c_idx] {
-            None => {
-                if company_score[c_idx].contains_key(&group.name) {
-                    company_partner[c_idx] = Some(g_idx);
-                    matched_groups[g_idx] = true;
-                } else {
-                    free_groups.push_back(g_idx);
-                }
-            }
-            Some(current_g_idx) => {
-                let score_new = company_score[c_idx].get(&group.name).copied().unwrap_or(i32::MAX);
-                let score_old = company_score[c_idx].get(&groups[current_g_idx].name).copied().unwrap_or(i32::MAX);
-                
-                if score_new < score_old {
-                    company_partner[c_idx] = Some(g_idx);
-                    matched_groups[g_idx] = true;
-                    matched_groups[current_g_idx] = false;
-                    free_groups.push_back(current_g_idx);
-                } else {
-                    free_groups.push_back(g_idx);
-                }
-            }
+        held[p_idx].push(g_idx);
+
+        if held[p_idx].len() > market.projects[p_idx].capacity {
+            let worst = (0..held[p_idx].len())
+                .max_by_key(|&i| market.company_rank(p_idx, held[p_idx][i]))
+                .unwrap();
+            free_groups.push_back(held[p_idx].swap_remove(worst));
         }
     }
-    
-    
-    // Wolne grupy
-    let unmatched_groups: Vec<usize> = matched_groups.iter()
-        .enumerate()
-        .filter(|(_, matched)| !**matched)
-        .map(|(idx, _)| idx)
-        .collect();
-    
-    // Wolne firmy
-    let free_companies: Vec<usize> = company_partner.iter() 
-        .enumerate()
-        .filter(|(_, partner)| partner.is_none())
-        .map(|(idx, _)| idx)
-        .collect();
-
-    // Jeśli nie ma wystarczająco wolnych firm, niektóre firmy dostaną 2 grupy
-    let mut extra_assignments = vec![];
-    
-    for (i, &g_idx) in unmatched_groups.iter().enumerate() {
-        let group = &groups[g_idx];
-        
-        if i < free_companies.len() {
-            let c_idx = free_companies[i];
-            company_partner[c_idx] = Some(g_idx);
-            matched_groups[g_idx] = true;
-        } else {
-            let mut best_company = None;
-            let mut best_score = i32::MAX;
-            
-            for c_idx in 0..companies.len() {
-                if let Some(score) = company_score[c_idx].get(&group.name) {
-                    if *score < best_score {
-                        best_score = *score;
-                        best_company = Some(c_idx);
+
+    let mut assignment = vec![None; market.groups.len()];
+    for (p_idx, held_groups) in held.iter().enumerate() {
+        for &g_idx in held_groups {
+            assignment[g_idx] = Some(p_idx);
+        }
+    }
+    assignment
+}
+
+// Wersja, w której proponują projekty (w kolejności preferencji swojej firmy)
+fn company_proposing(market: &Market) -> Vec<Option<usize>> {
+    let mut assignment: Vec<Option<usize>> = vec![None; market.groups.len()];
+    let mut next_offer: Vec<usize> = vec![0; market.projects.len()];
+    let mut held: Vec<usize> = vec![0; market.projects.len()];
+    let mut active: VecDeque<usize> = (0..market.projects.len()).collect();
+
+    while let Some(p_idx) = active.pop_front() {
+        let prefs = &market.company_prefs[market.project_company[p_idx]];
+
+        while held[p_idx] < market.projects[p_idx].capacity && next_offer[p_idx] < prefs.len() {
+            let g_idx = prefs[next_offer[p_idx]];
+            next_offer[p_idx] += 1;
+
+            let Some(rank_new) = market.group_rank(g_idx, p_idx) else {
+                continue;
+            };
+
+            match assignment[g_idx] {
+                None => {
+                    assignment[g_idx] = Some(p_idx);
+                    held[p_idx] += 1;
+                }
+                Some(current) => {
+                    let rank_old = market.group_rank(g_idx, current).unwrap();
+                    if rank_new < rank_old {
+                        assignment[g_idx] = Some(p_idx);
+                        held[p_idx] += 1;
+                        held[current] -= 1;
+                        active.push_back(current);
                     }
                 }
             }
-            
-            if let Some(c_idx) = best_company {
-                extra_assignments.push((group.name.clone(), companies[c_idx].name.clone()));
-            } else {
-                let c_idx = 0;
-                extra_assignments.push((group.name.clone(), companies[c_idx].name.clone()));
-            }
         }
     }
+    assignment
+}
 
-    
-    let mut results = Vec::new();
-    
-    for (c_idx, g_idx_opt) in company_partner.iter().enumerate() {
-        if let Some(g_idx) = g_idx_opt {
-            results.push(MatchResult {
-                group: groups[*g_idx].name.clone(),
-                company: companies[c_idx].name.clone(),
-            });
-        }
+pub fn stable_matching(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    proposer: Proposer,
+) -> Vec<MatchResult> {
+    if groups.is_empty() || companies.is_empty() {
+        return vec![];
     }
-    for (group_name, company_name) in extra_assignments {
-        results.push(MatchResult {
-            group: group_name,
-            company: company_name,
-        });
+
+    let market = Market::new(groups, companies, projects);
+    let assignment = match proposer {
+        Proposer::Groups => group_proposing(&market),
+        Proposer::Companies => company_proposing(&market),
+    };
+    market.results(&assignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::{group, company, project};
+
+    #[test]
+    fn projects_of_missing_companies_are_skipped() {
+        let groups = vec![group("g1", &["gone-p", "c1"])];
+        let companies = vec![company("c1", &["g1"])];
+        let projects = vec![project("gone-p", "gone", 1), project("c1-p", "c1", 1)];
+
+        let market = Market::new(&groups, &companies, &projects);
+        assert_eq!(market.projects.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), ["c1-p"]);
+
+        let matching = stable_matching(&groups, &companies, &projects, Proposer::Groups);
+        assert_eq!(matching.iter().map(|m| m.project.as_str()).collect::<Vec<_>>(), ["c1-p"]);
+        assert!(stable_matching(&[], &companies, &projects, Proposer::Groups).is_empty());
+    }
+
+    #[test]
+    fn results_carry_ranks_and_explanations() {
+        let groups = vec![group("g1", &["c1", "c2"]), group("g2", &["c1", "c2"])];
+        let companies = vec![company("c1", &["g1", "g2"]), company("c2", &["g2"])];
+
+        let matching = stable_matching(&groups, &companies, &[], Proposer::Groups);
+        let g2 = matching.iter().find(|m| m.group == "g2").unwrap();
+
+        assert_eq!((g2.group_rank, g2.company_rank), (Some(2), Some(1)));
+        assert!(g2.mutually_acceptable && !g2.fallback);
+        assert_eq!(g2.explanations.len(), 1);
+        assert!(g2.explanations[0].contains("'g1' (#1)"), "{:?}", g2.explanations);
+        assert!(matching.iter().find(|m| m.group == "g1").unwrap().explanations.is_empty());
     }
-    results.sort_by(|a, b| a.group.cmp(&b.group));
-    results
-}
\ No newline at end of file
+}
@@ -0,0 +1,411 @@
+use super::{Market, verify_stability};
+use crate::models::{Group, Company, Project, MatchResult, Algorithm, RankProfile};
+
+// Graf grupa -> miejsce (projekt rozbity na `capacity` miejsc), tylko pary akceptowalne dla obu stron.
+// Krawędź niesie poziom, na którym grupa postawiła projekt.
+struct SlotGraph {
+    slot_project: Vec<usize>,
+    edges: Vec<Vec<(usize, usize)>>,
+}
+
+impl SlotGraph {
+    fn new(market: &Market) -> Self {
+        let mut slot_project = Vec::new();
+        let mut project_slots = vec![Vec::new(); market.projects.len()];
+        for (p_idx, project) in market.projects.iter().enumerate() {
+            for _ in 0..project.capacity {
+                project_slots[p_idx].push(slot_project.len());
+                slot_project.push(p_idx);
+            }
+        }
+
+        let edges = (0..market.groups.len())
+            .map(|g_idx| market.group_prefs[g_idx].iter()
+                .zip(&market.group_tiers[g_idx])
+                .filter(|&(&p_idx, _)| market.company_rank(p_idx, g_idx).is_some())
+                .flat_map(|(&p_idx, &tier)| project_slots[p_idx].iter().map(move |&s_idx| (s_idx, tier)))
+                .collect())
+            .collect();
+
+        SlotGraph { slot_project, edges }
+    }
+
+    fn project_assignment(&self, matching: &[Option<usize>]) -> Vec<Option<usize>> {
+        matching.iter()
+            .map(|s_idx| s_idx.map(|s_idx| self.slot_project[s_idx]))
+            .collect()
+    }
+}
+
+fn try_augment(
+    g_idx: usize,
+    adjacency: &[Vec<usize>],
+    mate_group: &mut [Option<usize>],
+    mate_slot: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &s_idx in &adjacency[g_idx] {
+        if visited[s_idx] {
+            continue;
+        }
+        visited[s_idx] = true;
+
+        let free = match mate_slot[s_idx] {
+            None => true,
+            Some(h_idx) => try_augment(h_idx, adjacency, mate_group, mate_slot, visited),
+        };
+        if free {
+            mate_group[g_idx] = Some(s_idx);
+            mate_slot[s_idx] = Some(g_idx);
+            return true;
+        }
+    }
+    false
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Label {
+    Even,
+    Odd,
+    Unreachable,
+}
+
+// Podział Gallaia-Edmondsa względem skojarzenia największego: wierzchołki osiągalne
+// z wolnych ścieżką naprzemienną parzystej / nieparzystej długości, reszta nieosiągalna
+fn partition(adjacency: &[Vec<usize>], mate_group: &[Option<usize>], mate_slot: &[Option<usize>]) -> (Vec<Label>, Vec<Label>) {
+    let mut slot_adjacency = vec![Vec::new(); mate_slot.len()];
+    for (g_idx, slots) in adjacency.iter().enumerate() {
+        for &s_idx in slots {
+            slot_adjacency[s_idx].push(g_idx);
+        }
+    }
+
+    let mut group_label = vec![Label::Unreachable; mate_group.len()];
+    let mut slot_label = vec![Label::Unreachable; mate_slot.len()];
+    // false = grupa, true = miejsce
+    let mut stack = Vec::new();
+
+    for (g_idx, mate) in mate_group.iter().enumerate() {
+        if mate.is_none() {
+            group_label[g_idx] = Label::Even;
+            stack.push((false, g_idx));
+        }
+    }
+    for (s_idx, mate) in mate_slot.iter().enumerate() {
+        if mate.is_none() {
+            slot_label[s_idx] = Label::Even;
+            stack.push((true, s_idx));
+        }
+    }
+
+    while let Some((is_slot, idx)) = stack.pop() {
+        if is_slot {
+            for &g_idx in &slot_adjacency[idx] {
+                if mate_group[g_idx] == Some(idx) || group_label[g_idx] != Label::Unreachable {
+                    continue;
+                }
+                group_label[g_idx] = Label::Odd;
+                if let Some(s_idx) = mate_group[g_idx].filter(|&s| slot_label[s] == Label::Unreachable) {
+                    slot_label[s_idx] = Label::Even;
+                    stack.push((true, s_idx));
+                }
+            }
+        } else {
+            for &s_idx in &adjacency[idx] {
+                if mate_group[idx] == Some(s_idx) || slot_label[s_idx] != Label::Unreachable {
+                    continue;
+                }
+                slot_label[s_idx] = Label::Odd;
+                if let Some(g_idx) = mate_slot[s_idx].filter(|&g| group_label[g] == Label::Unreachable) {
+                    group_label[g_idx] = Label::Even;
+                    stack.push((false, g_idx));
+                }
+            }
+        }
+    }
+    (group_label, slot_label)
+}
+
+// Irving, Kavitha, Mehlhorn, Michail, Paluch: najwięcej pierwszych wyborów, potem drugich itd.
+fn rank_maximal(market: &Market) -> Vec<Option<usize>> {
+    let graph = SlotGraph::new(market);
+    let slot_count = graph.slot_project.len();
+    let max_rank = graph.edges.iter().flatten().map(|&(_, rank)| rank).max();
+
+    let mut future = graph.edges.clone();
+    let mut current: Vec<Vec<usize>> = vec![Vec::new(); market.groups.len()];
+    let mut mate_group = vec![None; market.groups.len()];
+    let mut mate_slot = vec![None; slot_count];
+
+    for rank in 0..=max_rank.unwrap_or(0) {
+        for (g_idx, edges) in future.iter_mut().enumerate() {
+            current[g_idx].extend(edges.iter().filter(|&&(_, r)| r == rank).map(|&(s_idx, _)| s_idx));
+            edges.retain(|&(_, r)| r > rank);
+        }
+
+        for g_idx in 0..market.groups.len() {
+            if mate_group[g_idx].is_none() {
+                let mut visited = vec![false; slot_count];
+                try_augment(g_idx, &current, &mut mate_group, &mut mate_slot, &mut visited);
+            }
+        }
+
+        let (group_label, slot_label) = partition(&current, &mate_group, &mate_slot);
+
+        // Wierzchołki nieparzyste i nieosiągalne są w każdym skojarzeniu rangowo-maksymalnym
+        // zajęte krawędziami dotychczasowych rang, więc tracą krawędzie dalszych rang
+        for (g_idx, edges) in future.iter_mut().enumerate() {
+            if group_label[g_idx] != Label::Even {
+                edges.clear();
+            }
+            edges.retain(|&(s_idx, _)| slot_label[s_idx] == Label::Even);
+        }
+        // Krawędzie nieparzysty-nieparzysty i nieparzysty-nieosiągalny nie należą do żadnego takiego skojarzenia
+        for (g_idx, slots) in current.iter_mut().enumerate() {
+            let g_label = group_label[g_idx];
+            slots.retain(|&s_idx| {
+                let s_label = slot_label[s_idx];
+                !(g_label == Label::Odd && s_label != Label::Even || s_label == Label::Odd && g_label != Label::Even)
+            });
+        }
+    }
+
+    graph.project_assignment(&mate_group)
+}
+
+// Węgierski (Kuhn-Munkres z potencjałami) dla macierzy n x m, n <= m; zwraca kolumnę dla każdego wiersza
+fn hungarian(cost: &[Vec<i64>]) -> Vec<usize> {
+    let n = cost.len();
+    let m = cost.first().map_or(0, |row| row.len());
+    let infinity = i64::MAX / 4;
+
+    let mut u = vec![0i64; n + 1];
+    let mut v = vec![0i64; m + 1];
+    let mut row_of = vec![0usize; m + 1];
+    let mut way = vec![0usize; m + 1];
+
+    for i in 1..=n {
+        row_of[0] = i;
+        let mut j0 = 0;
+        let mut min_v = vec![infinity; m + 1];
+        let mut used = vec![false; m + 1];
+
+        loop {
+            used[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = infinity;
+            let mut j1 = 0;
+
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let reduced = cost[i0 - 1][j - 1] - u[i0] - v[j];
+                if reduced < min_v[j] {
+                    min_v[j] = reduced;
+                    way[j] = j0;
+                }
+                if min_v[j] < delta {
+                    delta = min_v[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_v[j] -= delta;
+                }
+            }
+
+            j0 = j1;
+            if row_of[j0] == 0 {
+                break;
+            }
+        }
+
+        while j0 != 0 {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+        }
+    }
+
+    let mut column = vec![0; n];
+    for j in 1..=m {
+        if row_of[j] != 0 {
+            column[row_of[j] - 1] = j - 1;
+        }
+    }
+    column
+}
+
+// Jak najwięcej skojarzonych grup, a wśród takich przydziałów najmniejsza suma pozycji obu stron
+fn min_cost(market: &Market) -> Vec<Option<usize>> {
+    let graph = SlotGraph::new(market);
+    let group_count = market.groups.len();
+    let slot_count = graph.slot_project.len();
+
+    let pair_cost = |g_idx: usize, s_idx: usize, tier: usize| {
+        let p_idx = graph.slot_project[s_idx];
+        (tier + market.company_tier(p_idx, g_idx).unwrap()) as i64
+    };
+    let max_pair_cost = graph.edges.iter()
+        .enumerate()
+        .flat_map(|(g_idx, edges)| edges.iter().map(move |&(s_idx, tier)| pair_cost(g_idx, s_idx, tier)))
+        .max()
+        .unwrap_or(0);
+
+    // Brak pary kosztuje więcej niż dowolna różnica sum, a para niedozwolona więcej niż wszyscy bez pary
+    let unmatched = (max_pair_cost + 1) * group_count as i64 + 1;
+    let forbidden = unmatched * (group_count as i64 + 1);
+
+    // Kolumny: miejsca, potem osobna kolumna „bez pary” dla każdej grupy
+    let cost: Vec<Vec<i64>> = (0..group_count)
+        .map(|g_idx| {
+            let mut row = vec![forbidden; slot_count + group_count];
+            for &(s_idx, tier) in &graph.edges[g_idx] {
+                row[s_idx] = pair_cost(g_idx, s_idx, tier);
+            }
+            row[slot_count + g_idx] = unmatched;
+            row
+        })
+        .collect();
+
+    let matching: Vec<Option<usize>> = hungarian(&cost).into_iter()
+        .map(|column| (column < slot_count).then_some(column))
+        .collect();
+    graph.project_assignment(&matching)
+}
+
+pub fn rank_maximal_matching(groups: &[Group], companies: &[Company], projects: &[Project]) -> Vec<MatchResult> {
+    if groups.is_empty() || companies.is_empty() {
+        return vec![];
+    }
+
+    let market = Market::new(groups, companies, projects);
+    market.results(&rank_maximal(&market))
+}
+
+pub fn min_cost_matching(groups: &[Group], companies: &[Company], projects: &[Project]) -> Vec<MatchResult> {
+    if groups.is_empty() || companies.is_empty() {
+        return vec![];
+    }
+
+    let market = Market::new(groups, companies, projects);
+    market.results(&min_cost(&market))
+}
+
+pub fn rank_profile(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    algorithm: Algorithm,
+    matching: &[MatchResult],
+) -> RankProfile {
+    let market = Market::new(groups, companies, projects);
+    let mut group_ranks = Vec::new();
+    let mut company_ranks = Vec::new();
+    let mut outside_preferences = 0;
+    let mut matched = 0;
+
+    let count = |ranks: &mut Vec<usize>, tier: usize| {
+        if ranks.len() <= tier {
+            ranks.resize(tier + 1, 0);
+        }
+        ranks[tier] += 1;
+    };
+
+    for m in matching {
+        let Some(g_idx) = groups.iter().position(|g| g.name == m.group) else {
+            continue;
+        };
+        let Some(p_idx) = market.projects.iter().position(|p| p.id == m.project) else {
+            continue;
+        };
+        matched += 1;
+
+        match (market.group_tier(g_idx, p_idx), market.company_tier(p_idx, g_idx)) {
+            (Some(group_tier), Some(company_tier)) => {
+                count(&mut group_ranks, group_tier);
+                count(&mut company_ranks, company_tier);
+            }
+            _ => outside_preferences += 1,
+        }
+    }
+
+    RankProfile {
+        algorithm,
+        group_ranks,
+        company_ranks,
+        outside_preferences,
+        unmatched: groups.len().saturating_sub(matched),
+        blocking_pairs: verify_stability(groups, companies, projects, matching).blocking_pairs.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::testing::{tied_instance, all_matchings};
+
+    // Liczba grup z każdą pozycją; porządek leksykograficzny wektorów = porządek rangowy
+    fn signature(market: &Market, assignment: &[Option<usize>]) -> Vec<usize> {
+        let mut signature = vec![0; market.projects.len() + 1];
+        for (g_idx, p_idx) in assignment.iter().enumerate() {
+            if let Some(p_idx) = p_idx {
+                signature[market.group_tier(g_idx, *p_idx).unwrap()] += 1;
+            }
+        }
+        signature
+    }
+
+    fn unmatched_and_cost(market: &Market, assignment: &[Option<usize>]) -> (usize, usize) {
+        assignment.iter()
+            .enumerate()
+            .fold((0, 0), |(unmatched, cost), (g_idx, p_idx)| match p_idx {
+                None => (unmatched + 1, cost),
+                Some(p_idx) => (
+                    unmatched,
+                    cost + market.group_tier(g_idx, *p_idx).unwrap() + market.company_tier(*p_idx, g_idx).unwrap(),
+                ),
+            })
+    }
+
+    fn is_valid(market: &Market, assignment: &[Option<usize>]) -> bool {
+        let acceptable = assignment.iter().enumerate().all(|(g_idx, p_idx)| {
+            p_idx.is_none_or(|p_idx| market.group_rank(g_idx, p_idx).is_some() && market.company_rank(p_idx, g_idx).is_some())
+        });
+        let within_capacity = (0..market.projects.len()).all(|p_idx| {
+            assignment.iter().filter(|&&p| p == Some(p_idx)).count() <= market.projects[p_idx].capacity
+        });
+        acceptable && within_capacity
+    }
+
+    #[test]
+    fn rank_maximal_has_the_best_signature() {
+        for seed in 0..300 {
+            let (groups, companies, projects) = tied_instance(seed);
+            let market = Market::new(&groups, &companies, &projects);
+            let found = rank_maximal(&market);
+
+            let best = all_matchings(&market).iter().map(|m| signature(&market, m)).max().unwrap();
+            assert!(is_valid(&market, &found), "seed {}", seed);
+            assert_eq!(signature(&market, &found), best, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn min_cost_is_maximum_with_the_lowest_total_rank() {
+        for seed in 0..300 {
+            let (groups, companies, projects) = tied_instance(seed);
+            let market = Market::new(&groups, &companies, &projects);
+            let found = min_cost(&market);
+
+            let best = all_matchings(&market).iter().map(|m| unmatched_and_cost(&market, m)).min().unwrap();
+            assert!(is_valid(&market, &found), "seed {}", seed);
+            assert_eq!(unmatched_and_cost(&market, &found), best, "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use super::{company_projects, eligible_lists, stable_lattice, verify_stability};
+use crate::models::{Group, Company, Project, MatchResult, PairConstraint, PairConstraints, LatticeReport, StabilityReport};
+
+// Instancja po uwzględnieniu ograniczeń administratora: przypięte grupy mają już miejsce
+// (i znikają z rynku), a zakazane pary są usunięte z obu list
+pub(super) struct Constrained {
+    pub groups: Vec<Group>,
+    pub companies: Vec<Company>,
+    pub projects: Vec<Project>,
+    pub pinned: Vec<MatchResult>,
+    pub notes: Vec<String>,
+}
+
+pub fn is_forbidden(forbidden: &[PairConstraint], group: &str, company: &str) -> bool {
+    forbidden.iter().any(|f| f.group == group && f.company == company)
+}
+
+// Firma, której dotyczy pozycja listy grupy (id projektu albo nazwa firmy)
+fn entry_company<'a>(pref: &'a str, projects: &'a [Project]) -> &'a str {
+    projects.iter()
+        .find(|p| p.id == pref)
+        .map_or(pref, |p| p.company.as_str())
+}
+
+// Usuwa pozycje listy razem z ich poziomami
+fn retain_entries(preferences: &mut Vec<String>, tiers: &mut Vec<usize>, keep: impl Fn(&str) -> bool) {
+    let strict = tiers.len() != preferences.len();
+    let kept: Vec<(String, usize)> = preferences.iter()
+        .zip(tiers.iter().copied().chain(std::iter::repeat(0)))
+        .filter(|(pref, _)| keep(pref))
+        .map(|(pref, tier)| (pref.clone(), tier))
+        .collect();
+
+    *preferences = kept.iter().map(|(pref, _)| pref.clone()).collect();
+    *tiers = if strict { vec![] } else { kept.into_iter().map(|(_, tier)| tier).collect() };
+}
+
+// Miejsce dla przypiętej grupy: wskazany projekt, inaczej najwyżej oceniony przez grupę projekt firmy,
+// inaczej pierwszy projekt firmy
+fn pinned_project(pin: &PairConstraint, group: &Group, projects: &[Project]) -> Option<usize> {
+    let own = |p: &Project| p.company == pin.company;
+
+    pin.project.as_ref()
+        .and_then(|id| projects.iter().position(|p| &p.id == id && own(p)))
+        .or_else(|| group.preferences.iter()
+            .find_map(|pref| projects.iter().position(|p| own(p) && (&p.id == pref || &p.company == pref))))
+        .or_else(|| projects.iter().position(own))
+}
+
+pub(super) fn apply_constraints(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    constraints: &PairConstraints,
+) -> Constrained {
+    let mut projects = company_projects(companies, projects);
+    let mut pinned = Vec::new();
+    let mut notes = Vec::new();
+
+    for pin in &constraints.pinned {
+        let Some(group) = groups.iter().find(|g| g.name == pin.group) else {
+            notes.push(format!("pin of '{}' to '{}' ignored: no such group", pin.group, pin.company));
+            continue;
+        };
+        let Some(p_idx) = pinned_project(pin, group, &projects) else {
+            notes.push(format!("pin of '{}' to '{}' ignored: no such company", pin.group, pin.company));
+            continue;
+        };
+
+        let project = &mut projects[p_idx];
+        if project.capacity == 0 {
+            notes.push(format!("pin of '{}' overfills project '{}'", pin.group, project.title));
+        }
+        project.capacity = project.capacity.saturating_sub(1);
+        project.min_capacity = project.min_capacity.saturating_sub(1);
+        notes.push(format!("'{}' pinned to '{}' ({})", pin.group, project.company, project.title));
+        pinned.push(MatchResult {
+            group: pin.group.clone(),
+            company: project.company.clone(),
+            project: project.id.clone(),
+            project_title: project.title.clone(),
+            pinned: true,
+            ..Default::default()
+        });
+    }
+
+    let pinned_tracks: Vec<(&str, Option<&str>)> = pinned.iter()
+        .map(|m| (
+            m.company.as_str(),
+            groups.iter().find(|g| g.name == m.group).and_then(|g| g.track.as_deref()),
+        ))
+        .collect();
+
+    let forbidden = &constraints.forbidden;
+    let groups = groups.iter()
+        .filter(|g| !pinned.iter().any(|m| m.group == g.name))
+        .map(|g| {
+            let mut group = g.clone();
+            retain_entries(&mut group.preferences, &mut group.tiers, |pref| {
+                !is_forbidden(forbidden, &g.name, entry_company(pref, &projects))
+            });
+            group
+        })
+        .collect();
+    let companies = companies.iter()
+        .map(|c| {
+            let mut company = c.clone();
+            let seats = pinned.iter().filter(|m| m.company == c.name).count();
+            company.min_groups = company.min_groups.saturating_sub(seats);
+            // Przypięta grupa z kierunku zajmuje jedno z miejsc zarezerwowanych dla niego
+            for reservation in &mut company.reserved {
+                let on_track = pinned_tracks.iter()
+                    .filter(|&&(company, track)| company == c.name && track == Some(reservation.track.as_str()))
+                    .count();
+                reservation.seats = reservation.seats.saturating_sub(on_track);
+            }
+            retain_entries(&mut company.preferences, &mut company.tiers, |group| {
+                !is_forbidden(forbidden, group, &c.name)
+            });
+            company
+        })
+        .collect();
+
+    Constrained { groups, companies, projects, pinned, notes }
+}
+
+// Krata stabilnych skojarzeń rynku, na którym liczy /match: po ograniczeniach administratora
+// i wymaganiach firm. Przypięte pary dochodzą do każdego skojarzenia (bez wpływu na sumy pozycji)
+pub fn constrained_lattice(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    constraints: &PairConstraints,
+    limit: usize,
+) -> LatticeReport {
+    let market = apply_constraints(groups, companies, projects, constraints);
+    let (groups, companies) = eligible_lists(&market.groups, &market.companies, &market.projects);
+    let mut lattice = stable_lattice(&groups, &companies, &market.projects, limit);
+    for matching in &mut lattice.matchings {
+        matching.results.extend(market.pinned.iter().cloned());
+    }
+    lattice
+}
+
+// Weryfikacja względem tego samego rynku: przypięte pary są poza nim, a zakazane nie mogą blokować
+pub fn verify_constrained(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    constraints: &PairConstraints,
+    matching: &[MatchResult],
+) -> StabilityReport {
+    let market = apply_constraints(groups, companies, projects, constraints);
+    let (groups, companies) = eligible_lists(&market.groups, &market.companies, &market.projects);
+    let free: Vec<MatchResult> = matching.iter()
+        .filter(|m| !market.pinned.iter().any(|pin| pin.group == m.group && pin.project == m.project))
+        .cloned()
+        .collect();
+    verify_stability(&groups, &companies, &market.projects, &free)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::testing::tied_instance;
+    use crate::matching::run_matching;
+    use crate::models::{Algorithm, MatchParams, Proposer, TieBreak, UnmatchedPolicy};
+
+    const ALGORITHMS: [Algorithm; 10] = [
+        Algorithm::GaleShapley,
+        Algorithm::Egalitarian,
+        Algorithm::MinimumRegret,
+        Algorithm::RankMaximal,
+        Algorithm::MinCost,
+        Algorithm::SerialDictatorship,
+        Algorithm::TopTradingCycles,
+        Algorithm::MinimalDisruption,
+        Algorithm::LowerQuotas,
+        Algorithm::TrackReserves,
+    ];
+
+    fn pair(group: &str, company: &str) -> PairConstraint {
+        PairConstraint { group: group.into(), company: company.into(), project: None }
+    }
+
+    #[test]
+    fn every_algorithm_respects_pins_and_forbidden_pairs() {
+        for seed in 0..40 {
+            let (groups, companies, projects) = tied_instance(seed);
+            let constraints = PairConstraints {
+                pinned: vec![pair(&groups[0].name, &companies[0].name)],
+                forbidden: vec![pair(&groups[1].name, &companies[companies.len() - 1].name)],
+            };
+
+            for algorithm in ALGORITHMS {
+                for ties in [TieBreak::ListOrder, TieBreak::StronglyStable] {
+                    for unmatched in [UnmatchedPolicy::Leave, UnmatchedPolicy::Random] {
+                        let params = MatchParams { algorithm, ties, unmatched, seed: Some(seed), ..Default::default() };
+                        // strongly_stable ma własny algorytm i odrzuca pozostałe
+                        let Some(response) = run_matching(&groups, &companies, &projects, &params, None, &constraints, None) else {
+                            assert_ne!(algorithm, Algorithm::GaleShapley);
+                            continue;
+                        };
+
+                        let first = response.results.iter().find(|m| m.group == groups[0].name).unwrap();
+                        assert!(first.pinned && first.company == companies[0].name);
+                        assert!(!response.results.iter().any(|m| is_forbidden(&constraints.forbidden, &m.group, &m.company)));
+                        for p in company_projects(&companies, &projects) {
+                            assert!(response.results.iter().filter(|m| m.project == p.id).count() <= p.capacity);
+                        }
+
+                        // Raport pokazuje dokładnie pary blokujące względem oryginalnych list
+                        let report = response.constraints.unwrap();
+                        let stability = verify_stability(&groups, &companies, &projects, &response.results);
+                        assert_eq!(report.blocking_pairs.len(), stability.blocking_pairs.len());
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn lattice_and_verification_follow_the_constraints() {
+        let pairs = |results: &[MatchResult]| {
+            let mut pairs: Vec<(String, String)> = results.iter().map(|m| (m.group.clone(), m.project.clone())).collect();
+            pairs.sort();
+            pairs
+        };
+        let mut raw_unstable = 0;
+
+        for seed in 0..100 {
+            let (mut groups, mut companies, projects) = tied_instance(seed);
+            groups.iter_mut().for_each(|g| g.tiers.clear());
+            companies.iter_mut().for_each(|c| c.tiers.clear());
+            let constraints = PairConstraints {
+                pinned: vec![pair(&groups[0].name, &companies[0].name)],
+                forbidden: vec![pair(&groups[1].name, &companies[companies.len() - 1].name)],
+            };
+            let lattice = constrained_lattice(&groups, &companies, &projects, &constraints, 1000);
+
+            for proposer in [Proposer::Groups, Proposer::Companies] {
+                let params = MatchParams { proposer, ..Default::default() };
+                let response = run_matching(&groups, &companies, &projects, &params, None, &constraints, None).unwrap();
+                let report = verify_constrained(&groups, &companies, &projects, &constraints, &response.results);
+                assert!(report.stable, "seed {}: {:?}", seed, report);
+                assert!(lattice.matchings.iter().any(|m| pairs(&m.results) == pairs(&response.results)), "seed {}", seed);
+                if !verify_stability(&groups, &companies, &projects, &response.results).stable {
+                    raw_unstable += 1;
+                }
+            }
+
+            for matching in &lattice.matchings {
+                assert!(matching.results.iter().any(|m| m.group == groups[0].name && m.pinned));
+                assert!(!matching.results.iter().any(|m| is_forbidden(&constraints.forbidden, &m.group, &m.company)));
+                assert!(verify_constrained(&groups, &companies, &projects, &constraints, &matching.results).stable, "seed {}", seed);
+            }
+        }
+        // Na surowych listach te same wyniki często wyglądają na niestabilne
+        assert!(raw_unstable > 0);
+    }
+}
@@ -0,0 +1,195 @@
+use super::company_projects;
+use crate::models::{Group, Company, Project, EligibilityReport, IneligibleGroup, MissingSkills};
+use std::collections::HashSet;
+
+fn normalize(skill: &str) -> String {
+    skill.trim().to_lowercase()
+}
+
+// Wymagania firmy i projektu, których grupa nie zgłosiła (bez rozróżniania wielkości liter)
+pub fn missing_skills(group: &Group, company: &Company, project: &Project) -> Vec<String> {
+    let has: HashSet<String> = group.skills.iter().map(|s| normalize(s)).collect();
+    let mut seen = HashSet::new();
+    company.required_skills.iter()
+        .chain(&project.required_skills)
+        .filter(|skill| !has.contains(&normalize(skill)) && seen.insert(normalize(skill)))
+        .cloned()
+        .collect()
+}
+
+fn eligible(group: &Group, companies: &[Company], project: &Project) -> bool {
+    companies.iter()
+        .find(|c| c.name == project.company)
+        .is_none_or(|company| missing_skills(group, company, project).is_empty())
+}
+
+// Listy obu stron ograniczone do par, w których grupa spełnia wymagania
+pub fn eligible_lists(groups: &[Group], companies: &[Company], projects: &[Project]) -> (Vec<Group>, Vec<Company>) {
+    restrict_lists(groups, companies, projects, |g, p| eligible(g, companies, p))
+}
+
+// Listy obu stron ograniczone do par (grupa, projekt) spełniających `acceptable`. Nazwa firmy na liście
+// grupy zostaje, gdy warunek obejmuje wszystkie jej projekty; inaczej zastępują ją pasujące projekty.
+// Firma zachowuje grupę, która pasuje do choć jednego jej projektu.
+pub(super) fn restrict_lists(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    acceptable: impl Fn(&Group, &Project) -> bool,
+) -> (Vec<Group>, Vec<Company>) {
+    let projects = company_projects(companies, projects);
+
+    let restricted_groups: Vec<Group> = groups.iter()
+        .map(|g| {
+            let strict = g.tiers.len() != g.preferences.len();
+            let mut preferences = Vec::new();
+            let mut tiers = Vec::new();
+
+            for (position, pref) in g.preferences.iter().enumerate() {
+                let entries: Vec<String> = match projects.iter().find(|p| &p.id == pref) {
+                    Some(project) => acceptable(g, project)
+                        .then(|| pref.clone())
+                        .into_iter()
+                        .collect(),
+                    None => {
+                        let own: Vec<&Project> = projects.iter().filter(|p| &p.company == pref).collect();
+                        let fitting: Vec<&Project> = own.iter().copied().filter(|p| acceptable(g, p)).collect();
+                        if fitting.len() == own.len() {
+                            vec![pref.clone()]
+                        } else {
+                            fitting.iter().map(|p| p.id.clone()).collect()
+                        }
+                    }
+                };
+                for entry in entries {
+                    if !strict {
+                        tiers.push(g.tiers[position]);
+                    }
+                    preferences.push(entry);
+                }
+            }
+            Group { preferences, tiers, ..g.clone() }
+        })
+        .collect();
+
+    let companies = companies.iter()
+        .map(|c| {
+            let own: Vec<&Project> = projects.iter().filter(|p| p.company == c.name).collect();
+            let fits = |name: &str| groups.iter().find(|g| g.name == name)
+                .is_none_or(|g| own.iter().any(|p| acceptable(g, p)));
+
+            let strict = c.tiers.len() != c.preferences.len();
+            let kept: Vec<(String, usize)> = c.preferences.iter()
+                .zip(c.tiers.iter().copied().chain(std::iter::repeat(0)))
+                .filter(|(name, _)| fits(name))
+                .map(|(name, tier)| (name.clone(), tier))
+                .collect();
+
+            Company {
+                preferences: kept.iter().map(|(name, _)| name.clone()).collect(),
+                tiers: if strict { vec![] } else { kept.into_iter().map(|(_, tier)| tier).collect() },
+                ..c.clone()
+            }
+        })
+        .collect();
+
+    (restricted_groups, companies)
+}
+
+// Które grupy nie spełniają wymagań firmy (albo części jej projektów) i czego im brakuje
+pub fn eligibility_report(groups: &[Group], company: &Company, projects: &[Project]) -> EligibilityReport {
+    let projects = company_projects(std::slice::from_ref(company), projects);
+    let mut eligible = Vec::new();
+    let mut ineligible = Vec::new();
+
+    for group in groups {
+        let missing: Vec<MissingSkills> = projects.iter()
+            .filter_map(|project| {
+                let skills = missing_skills(group, company, project);
+                (!skills.is_empty()).then(|| MissingSkills {
+                    project: project.id.clone(),
+                    project_title: project.title.clone(),
+                    skills,
+                })
+            })
+            .collect();
+
+        if missing.is_empty() {
+            eligible.push(group.name.clone());
+        } else {
+            ineligible.push(IneligibleGroup {
+                group: group.name.clone(),
+                eligible_elsewhere: missing.len() < projects.len(),
+                missing,
+            });
+        }
+    }
+
+    EligibilityReport {
+        company: company.name.clone(),
+        required_skills: company.required_skills.clone(),
+        eligible,
+        ineligible,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{stable_matching, testing::tied_instance};
+    use crate::models::Proposer;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    const SKILLS: [&str; 3] = ["rust", "ml", "web"];
+
+    fn random_skills(rng: &mut ChaCha8Rng, up_to: usize) -> Vec<String> {
+        SKILLS.iter()
+            .filter(|_| rng.gen_range(0..3) < up_to)
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn only_eligible_pairs_are_matched() {
+        for seed in 0..300 {
+            let (mut groups, mut companies, mut projects) = tied_instance(seed);
+            let mut rng = ChaCha8Rng::seed_from_u64(seed + 3000);
+            // Drugi projekt każdej firmy, żeby nazwa firmy na liście grupy obejmowała różne wymagania
+            let second: Vec<Project> = projects.iter()
+                .map(|p| Project { id: format!("{}-b", p.id), ..p.clone() })
+                .collect();
+            projects.extend(second);
+            for g in &mut groups {
+                g.skills = random_skills(&mut rng, 2);
+            }
+            for c in &mut companies {
+                c.required_skills = random_skills(&mut rng, 1);
+            }
+            for p in &mut projects {
+                p.required_skills = random_skills(&mut rng, 1).into_iter().map(|s| s.to_uppercase()).collect();
+            }
+
+            let (eligible_groups, eligible_companies) = eligible_lists(&groups, &companies, &projects);
+            for proposer in [Proposer::Groups, Proposer::Companies] {
+                for m in stable_matching(&eligible_groups, &eligible_companies, &projects, proposer) {
+                    let group = groups.iter().find(|g| g.name == m.group).unwrap();
+                    let company = companies.iter().find(|c| c.name == m.company).unwrap();
+                    let project = company_projects(&companies, &projects).into_iter().find(|p| p.id == m.project).unwrap();
+                    assert!(missing_skills(group, company, &project).is_empty(), "seed {}", seed);
+                }
+            }
+
+            // Bez wymagań listy zostają bez zmian
+            for c in &mut companies {
+                c.required_skills.clear();
+            }
+            for p in &mut projects {
+                p.required_skills.clear();
+            }
+            let (same_groups, same_companies) = eligible_lists(&groups, &companies, &projects);
+            assert!(same_groups.iter().zip(&groups).all(|(a, b)| a.preferences == b.preferences && a.tiers == b.tiers));
+            assert!(same_companies.iter().zip(&companies).all(|(a, b)| a.preferences == b.preferences && a.tiers == b.tiers));
+        }
+    }
+}
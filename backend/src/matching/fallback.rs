@@ -0,0 +1,197 @@
+use super::{Market, is_forbidden, eligibility::missing_skills, shortlist::shortlisted};
+use crate::models::{Group, Company, Project, MatchResult, MatchParams, PairConstraint, UnmatchedPolicy, Application};
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+// Projekty dopuszczalne dla grupy bez pary: najpierw te z jej listy,
+// potem projekty firm, które ją wpisały (w kolejności ich list)
+fn acceptable_projects(market: &Market, g_idx: usize) -> Vec<usize> {
+    let mut candidates = market.group_prefs[g_idx].clone();
+
+    let mut ranked_by: Vec<(usize, usize)> = (0..market.companies.len())
+        .filter_map(|c_idx| market.company_prefs[c_idx].iter()
+            .position(|&g| g == g_idx)
+            .map(|rank| (market.company_tiers[c_idx][rank], c_idx)))
+        .collect();
+    ranked_by.sort();
+
+    for (_, c_idx) in ranked_by {
+        for (p_idx, &pc) in market.project_company.iter().enumerate() {
+            if pc == c_idx && !candidates.contains(&p_idx) {
+                candidates.push(p_idx);
+            }
+        }
+    }
+    candidates
+}
+
+fn eligible(market: &Market, g_idx: usize, p_idx: usize) -> bool {
+    let company = &market.companies[market.project_company[p_idx]];
+    missing_skills(&market.groups[g_idx], company, &market.projects[p_idx]).is_empty()
+}
+
+// Dosadza grupy na dopuszczalne projekty, pozwalając przekroczyć pojemność o `extra`
+fn fill(market: &Market, placed: &mut [Option<usize>], unmatched: &[usize], held: &mut [usize], extra: usize) {
+    for &g_idx in unmatched {
+        if placed[g_idx].is_some() {
+            continue;
+        }
+        let found = acceptable_projects(market, g_idx).into_iter()
+            .find(|&p_idx| held[p_idx] < market.projects[p_idx].capacity + extra && eligible(market, g_idx, p_idx));
+        if let Some(p_idx) = found {
+            placed[g_idx] = Some(p_idx);
+            held[p_idx] += 1;
+        }
+    }
+}
+
+// Zwraca wyniki uzupełnione o przydziały awaryjne (`fallback`) i listę grup, które zostały bez pary.
+// Przy `shortlist` losowy przydział wybiera tylko pary z zakwalifikowanym zgłoszeniem.
+pub fn place_unmatched(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    results: Vec<MatchResult>,
+    params: &MatchParams,
+    forbidden: &[PairConstraint],
+    shortlist: Option<&[Application]>,
+) -> (Vec<MatchResult>, Vec<String>) {
+    let market = Market::new(groups, companies, projects);
+    let mut matched = vec![false; groups.len()];
+    let mut held = vec![0; market.projects.len()];
+
+    for m in &results {
+        if let Some(g_idx) = groups.iter().position(|g| g.name == m.group) {
+            matched[g_idx] = true;
+        }
+        if let Some(p_idx) = market.projects.iter().position(|p| p.id == m.project) {
+            held[p_idx] += 1;
+        }
+    }
+
+    let unmatched: Vec<usize> = (0..groups.len()).filter(|&g_idx| !matched[g_idx]).collect();
+    let mut placed: Vec<Option<usize>> = vec![None; groups.len()];
+
+    match params.unmatched {
+        UnmatchedPolicy::Leave => {}
+        UnmatchedPolicy::FreeAcceptable => fill(&market, &mut placed, &unmatched, &mut held, 0),
+        UnmatchedPolicy::Overflow => {
+            fill(&market, &mut placed, &unmatched, &mut held, 0);
+            fill(&market, &mut placed, &unmatched, &mut held, params.overflow_limit.unwrap_or(1));
+        }
+        UnmatchedPolicy::Random => {
+            let mut rng = ChaCha8Rng::seed_from_u64(params.seed.unwrap_or_default());
+            for &g_idx in &unmatched {
+                let free: Vec<usize> = (0..market.projects.len())
+                    .filter(|&p_idx| held[p_idx] < market.projects[p_idx].capacity)
+                    .filter(|&p_idx| !is_forbidden(forbidden, &groups[g_idx].name, &market.projects[p_idx].company))
+                    .filter(|&p_idx| eligible(&market, g_idx, p_idx))
+                    .filter(|&p_idx| shortlist.is_none_or(|a| shortlisted(a, &groups[g_idx].name, &market.projects[p_idx])))
+                    .collect();
+                if let Some(&p_idx) = free.choose(&mut rng) {
+                    placed[g_idx] = Some(p_idx);
+                    held[p_idx] += 1;
+                }
+            }
+        }
+    }
+
+    let still_unmatched = unmatched.iter()
+        .filter(|&&g_idx| placed[g_idx].is_none())
+        .map(|&g_idx| groups[g_idx].name.clone())
+        .collect();
+
+    let mut results = results;
+    results.extend(market.results(&placed).into_iter().map(|m| MatchResult { fallback: true, ..m }));
+    results.sort_by(|a, b| a.group.cmp(&b.group));
+    // Wyjaśnienia uwzględniają dosadzone grupy
+    market.annotate(&mut results);
+
+    (results, still_unmatched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{stable_matching, run_matching, shortlisted_lists};
+    use crate::matching::testing::{group, company, project};
+    use crate::models::{Proposer, ApplicationStatus};
+
+    fn instance() -> (Vec<Group>, Vec<Company>, Vec<Project>) {
+        // g3 zostaje bez pary; c2 ma wolne miejsce, ale nikt go nie wpisał
+        let groups = vec![group("g1", &["c1"]), group("g2", &["c1"]), group("g3", &["c1"])];
+        let companies = vec![company("c1", &["g1", "g2", "g3"]), company("c2", &[])];
+        let projects = vec![project("c1", "c1", 2)];
+        (groups, companies, projects)
+    }
+
+    fn run(unmatched: UnmatchedPolicy, overflow_limit: usize) -> (Vec<MatchResult>, Vec<String>) {
+        let (groups, companies, projects) = instance();
+        let results = stable_matching(&groups, &companies, &projects, Proposer::Groups);
+        let params = MatchParams { unmatched, overflow_limit: Some(overflow_limit), seed: Some(1), ..Default::default() };
+        place_unmatched(&groups, &companies, &projects, results, &params, &[], None)
+    }
+
+    #[test]
+    fn leave_and_free_acceptable_do_not_place_outside_preferences() {
+        for policy in [UnmatchedPolicy::Leave, UnmatchedPolicy::FreeAcceptable] {
+            let (results, unmatched) = run(policy, 1);
+            assert_eq!(results.len(), 2);
+            assert!(results.iter().all(|m| !m.fallback));
+            assert_eq!(unmatched, vec!["g3".to_string()]);
+        }
+    }
+
+    #[test]
+    fn overflow_respects_the_limit() {
+        let (results, unmatched) = run(UnmatchedPolicy::Overflow, 1);
+        let g3 = results.iter().find(|m| m.group == "g3").unwrap();
+        assert!(g3.fallback && g3.project == "c1");
+        assert!(unmatched.is_empty());
+
+        let (results, unmatched) = run(UnmatchedPolicy::Overflow, 0);
+        assert_eq!(results.len(), 2);
+        assert_eq!(unmatched, vec!["g3".to_string()]);
+    }
+
+    #[test]
+    fn random_uses_only_free_seats() {
+        let (results, unmatched) = run(UnmatchedPolicy::Random, 1);
+        let g3 = results.iter().find(|m| m.group == "g3").unwrap();
+        assert!(g3.fallback && g3.company == "c2");
+        assert!(unmatched.is_empty());
+    }
+
+    #[test]
+    fn random_keeps_to_the_shortlist() {
+        let (groups, companies, projects) = instance();
+        let application = |group: &str, company: &str, status| Application {
+            group: group.into(),
+            company: company.into(),
+            project: None,
+            motivation: String::new(),
+            status,
+        };
+        let mut applications = vec![
+            application("g1", "c1", ApplicationStatus::Shortlisted),
+            application("g2", "c1", ApplicationStatus::Shortlisted),
+            application("g3", "c1", ApplicationStatus::Shortlisted),
+            application("g3", "c2", ApplicationStatus::Pending),
+        ];
+        let params = MatchParams { unmatched: UnmatchedPolicy::Random, ..Default::default() };
+
+        for seed in 0..20 {
+            let params = MatchParams { seed: Some(seed), ..params.clone() };
+            let (shortlisted_groups, shortlisted_companies) = shortlisted_lists(&groups, &companies, &projects, &applications);
+            let response = run_matching(&shortlisted_groups, &shortlisted_companies, &projects, &params, None, &Default::default(), Some(&applications)).unwrap();
+            assert_eq!(response.unmatched, vec!["g3".to_string()], "seed {}", seed);
+        }
+
+        applications[3].status = ApplicationStatus::Shortlisted;
+        let (shortlisted_groups, shortlisted_companies) = shortlisted_lists(&groups, &companies, &projects, &applications);
+        let response = run_matching(&shortlisted_groups, &shortlisted_companies, &projects, &params, None, &Default::default(), Some(&applications)).unwrap();
+        let g3 = response.results.iter().find(|m| m.group == "g3").unwrap();
+        assert!(g3.fallback && g3.company == "c2");
+    }
+}
@@ -0,0 +1,393 @@
+use super::Market;
+use crate::models::{Group, Company, Project, StableMatching, LatticeReport};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// Rotacja: pary (grupa, miejsce), po eliminacji grupa i dostaje miejsce grupy i + 1
+pub(super) type Rotation = Vec<(usize, usize)>;
+
+// Wszystkie rotacje w kolejności eliminacji od skojarzenia optymalnego dla grup
+// (to rozszerzenie liniowe porządku) razem z bezpośrednimi poprzednikami każdej z nich
+pub(super) struct RotationPoset {
+    pub start: Vec<Option<usize>>,
+    pub rotations: Vec<Rotation>,
+    pub predecessors: Vec<Vec<usize>>,
+    // Dla każdego miejsca: rotacje dające mu nową grupę, w kolejności
+    pub slot_moves: Vec<Vec<(usize, usize)>>,
+}
+
+// Instancja jeden-do-jednego: każdy projekt rozbity na `capacity` miejsc.
+// Grupy ustawiają miejsca jednego projektu zawsze w tej samej kolejności,
+// dzięki czemu stabilne skojarzenia obu instancji odpowiadają sobie wzajemnie.
+pub(super) struct SlotMarket {
+    pub slot_project: Vec<usize>,
+    pub group_prefs: Vec<Vec<usize>>,
+    group_rank: Vec<Vec<Option<usize>>>,
+    slot_rank: Vec<Vec<Option<usize>>>,
+}
+
+impl SlotMarket {
+    pub fn new(market: &Market) -> Self {
+        let mut slot_project = Vec::new();
+        let mut project_slots = vec![Vec::new(); market.projects.len()];
+
+        for (p_idx, project) in market.projects.iter().enumerate() {
+            for _ in 0..project.capacity {
+                project_slots[p_idx].push(slot_project.len());
+                slot_project.push(p_idx);
+            }
+        }
+
+        // Zostają tylko pary akceptowalne dla obu stron
+        let group_prefs: Vec<Vec<usize>> = (0..market.groups.len())
+            .map(|g_idx| market.group_prefs[g_idx].iter()
+                .filter(|&&p_idx| market.company_rank(p_idx, g_idx).is_some())
+                .flat_map(|&p_idx| project_slots[p_idx].iter().copied())
+                .collect())
+            .collect();
+
+        let slot_prefs: Vec<Vec<usize>> = slot_project.iter()
+            .map(|&p_idx| {
+                let mut seen = HashSet::new();
+                market.company_prefs[market.project_company[p_idx]].iter()
+                    .copied()
+                    .filter(|&g_idx| market.group_rank(g_idx, p_idx).is_some() && seen.insert(g_idx))
+                    .collect()
+            })
+            .collect();
+
+        let mut group_rank = vec![vec![None; slot_project.len()]; market.groups.len()];
+        for (g_idx, prefs) in group_prefs.iter().enumerate() {
+            for (rank, &s_idx) in prefs.iter().enumerate() {
+                group_rank[g_idx][s_idx] = Some(rank);
+            }
+        }
+
+        let mut slot_rank = vec![vec![None; market.groups.len()]; slot_project.len()];
+        for (s_idx, prefs) in slot_prefs.iter().enumerate() {
+            for (rank, &g_idx) in prefs.iter().enumerate() {
+                slot_rank[s_idx][g_idx] = Some(rank);
+            }
+        }
+
+        SlotMarket {
+            slot_project,
+            group_prefs,
+            group_rank,
+            slot_rank,
+        }
+    }
+
+    pub fn slot_partners(&self, matching: &[Option<usize>]) -> Vec<Option<usize>> {
+        let mut partners = vec![None; self.slot_project.len()];
+        for (g_idx, s_idx) in matching.iter().enumerate() {
+            if let Some(s_idx) = s_idx {
+                partners[*s_idx] = Some(g_idx);
+            }
+        }
+        partners
+    }
+
+    // Skojarzenie optymalne dla grup (grupy proponują)
+    pub fn group_optimal(&self) -> Vec<Option<usize>> {
+        let mut matching = vec![None; self.group_prefs.len()];
+        let mut partners: Vec<Option<usize>> = vec![None; self.slot_project.len()];
+        let mut next_proposal = vec![0; self.group_prefs.len()];
+        let mut free_groups: VecDeque<usize> = (0..self.group_prefs.len()).collect();
+
+        while let Some(g_idx) = free_groups.pop_front() {
+            let Some(&s_idx) = self.group_prefs[g_idx].get(next_proposal[g_idx]) else {
+                continue;
+            };
+            next_proposal[g_idx] += 1;
+
+            match partners[s_idx] {
+                None => {
+                    partners[s_idx] = Some(g_idx);
+                    matching[g_idx] = Some(s_idx);
+                }
+                Some(h_idx) if self.slot_rank[s_idx][g_idx] < self.slot_rank[s_idx][h_idx] => {
+                    partners[s_idx] = Some(g_idx);
+                    matching[g_idx] = Some(s_idx);
+                    matching[h_idx] = None;
+                    free_groups.push_back(h_idx);
+                }
+                Some(_) => free_groups.push_back(g_idx),
+            }
+        }
+        matching
+    }
+
+    pub fn exposed_rotations(&self, matching: &[Option<usize>]) -> Vec<Rotation> {
+        let partners = self.slot_partners(matching);
+
+        // next[g] = grupa trzymana przez pierwsze miejsce za M(g), które woli g od swojej grupy.
+        // Wolne miejsce przyjęłoby g od razu, więc g nie może należeć do żadnej rotacji.
+        let next: Vec<Option<usize>> = matching.iter()
+            .enumerate()
+            .map(|(g_idx, s_idx)| {
+                let start = self.group_rank[g_idx][(*s_idx)?]? + 1;
+                for &s in &self.group_prefs[g_idx][start..] {
+                    let h_idx = partners[s]?;
+                    if self.slot_rank[s][g_idx] < self.slot_rank[s][h_idx] {
+                        return Some(h_idx);
+                    }
+                }
+                None
+            })
+            .collect();
+
+        // Cykle w grafie g -> next[g] to właśnie rotacje
+        let mut state = vec![0u8; matching.len()];
+        let mut rotations = Vec::new();
+
+        for start in 0..matching.len() {
+            let mut path = Vec::new();
+            let mut current = Some(start);
+
+            while let Some(g_idx) = current {
+                if state[g_idx] != 0 {
+                    break;
+                }
+                state[g_idx] = 1;
+                path.push(g_idx);
+                current = next[g_idx];
+            }
+
+            if let Some(g_idx) = current.filter(|&g_idx| state[g_idx] == 1) {
+                let cycle_start = path.iter().position(|&x| x == g_idx).unwrap();
+                rotations.push(path[cycle_start..].iter()
+                    .map(|&g| (g, matching[g].unwrap()))
+                    .collect());
+            }
+            for g_idx in path {
+                state[g_idx] = 2;
+            }
+        }
+        rotations
+    }
+
+    pub fn eliminate(&self, matching: &mut [Option<usize>], rotation: &Rotation) {
+        for i in 0..rotation.len() {
+            let (g_idx, _) = rotation[i];
+            let (_, s_next) = rotation[(i + 1) % rotation.len()];
+            matching[g_idx] = Some(s_next);
+        }
+    }
+
+    pub fn rotation_poset(&self) -> RotationPoset {
+        let start = self.group_optimal();
+        let mut matching = start.clone();
+        let mut rotations: Vec<Rotation> = Vec::new();
+
+        while let Some(rotation) = self.exposed_rotations(&matching).into_iter().next() {
+            self.eliminate(&mut matching, &rotation);
+            rotations.push(rotation);
+        }
+
+        let mut produced_by: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut slot_moves = vec![Vec::new(); self.slot_project.len()];
+        for (r_idx, rotation) in rotations.iter().enumerate() {
+            for i in 0..rotation.len() {
+                let (g_idx, _) = rotation[i];
+                let (_, s_next) = rotation[(i + 1) % rotation.len()];
+                produced_by.insert((g_idx, s_next), r_idx);
+                slot_moves[s_next].push((r_idx, g_idx));
+            }
+        }
+
+        let start_partners = self.slot_partners(&start);
+        let mut predecessors = vec![Vec::new(); rotations.len()];
+
+        for (r_idx, rotation) in rotations.iter().enumerate() {
+            for i in 0..rotation.len() {
+                let (g_idx, s_idx) = rotation[i];
+                let (_, s_next) = rotation[(i + 1) % rotation.len()];
+
+                // Para (g, s) musiała najpierw powstać
+                if let Some(&p_idx) = produced_by.get(&(g_idx, s_idx)) {
+                    predecessors[r_idx].push(p_idx);
+                }
+
+                // Miejsca pominięte przez g muszą już mieć grupę lepszą niż g
+                let from = self.group_rank[g_idx][s_idx].unwrap();
+                let to = self.group_rank[g_idx][s_next].unwrap();
+                for &between in &self.group_prefs[g_idx][from + 1..to] {
+                    let better_than_g = |h_idx: usize| {
+                        self.slot_rank[between][h_idx] < self.slot_rank[between][g_idx]
+                    };
+                    if start_partners[between].is_some_and(better_than_g) {
+                        continue;
+                    }
+                    if let Some(&(p_idx, _)) = slot_moves[between].iter().find(|&&(_, h)| better_than_g(h)) {
+                        predecessors[r_idx].push(p_idx);
+                    }
+                }
+            }
+            predecessors[r_idx].sort();
+            predecessors[r_idx].dedup();
+        }
+
+        RotationPoset {
+            start,
+            rotations,
+            predecessors,
+            slot_moves,
+        }
+    }
+
+    // Skojarzenie odpowiadające zamkniętemu zbiorowi rotacji
+    pub fn apply(&self, poset: &RotationPoset, chosen: &[bool]) -> Vec<Option<usize>> {
+        let mut matching = poset.start.clone();
+        for (rotation, _) in poset.rotations.iter().zip(chosen).filter(|(_, c)| **c) {
+            self.eliminate(&mut matching, rotation);
+        }
+        matching
+    }
+
+    pub fn project_assignment(&self, matching: &[Option<usize>]) -> Vec<Option<usize>> {
+        matching.iter()
+            .map(|s_idx| s_idx.map(|s_idx| self.slot_project[s_idx]))
+            .collect()
+    }
+}
+
+pub(super) fn summarize(market: &Market, assignment: &[Option<usize>]) -> StableMatching {
+    let mut group_rank_sum = 0;
+    let mut company_rank_sum = 0;
+
+    for (g_idx, p_idx) in assignment.iter().enumerate() {
+        if let Some(p_idx) = p_idx {
+            group_rank_sum += market.group_rank(g_idx, *p_idx).map_or(0, |r| r + 1);
+            company_rank_sum += market.company_rank(*p_idx, g_idx).map_or(0, |r| r + 1);
+        }
+    }
+
+    StableMatching {
+        group_rank_sum,
+        company_rank_sum,
+        results: market.results(assignment),
+    }
+}
+
+pub fn stable_lattice(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    limit: usize,
+) -> LatticeReport {
+    if groups.is_empty() || companies.is_empty() {
+        return LatticeReport { truncated: false, matchings: vec![] };
+    }
+
+    let market = Market::new(groups, companies, projects);
+    let slots = SlotMarket::new(&market);
+
+    let start = slots.group_optimal();
+    let mut seen = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([start]);
+    let mut matchings = Vec::new();
+
+    // Przechodzimy kratę od skojarzenia optymalnego dla grup, eliminując odsłonięte rotacje
+    while matchings.len() < limit {
+        let Some(matching) = queue.pop_front() else {
+            break;
+        };
+
+        for rotation in slots.exposed_rotations(&matching) {
+            let mut next = matching.clone();
+            slots.eliminate(&mut next, &rotation);
+            if seen.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+
+        matchings.push(summarize(&market, &slots.project_assignment(&matching)));
+    }
+
+    LatticeReport {
+        truncated: !queue.is_empty(),
+        matchings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{stable_matching, verify_stability};
+    use crate::matching::testing::{group, company, assign, random_instance};
+    use crate::models::{MatchResult, Proposer};
+
+    #[test]
+    fn every_lattice_member_is_stable() {
+        for seed in 1..100 {
+            let (groups, companies, projects) = random_instance(seed);
+            let lattice = stable_lattice(&groups, &companies, &projects, 1000);
+            assert!(!lattice.truncated);
+
+            for stable in &lattice.matchings {
+                let report = verify_stability(&groups, &companies, &projects, &stable.results);
+                assert!(report.stable, "seed {}: {:?}", seed, report);
+            }
+        }
+    }
+
+    #[test]
+    fn lattice_finds_every_stable_matching() {
+        for seed in 1..25 {
+            let (mut groups, companies, projects) = random_instance(seed);
+            groups.truncate(5);
+            let projects: Vec<Project> = projects.into_iter().take(4).collect();
+
+            // Wszystkie przypisania grupa -> projekt albo brak, sprawdzone weryfikatorem
+            let mut brute_force = 0;
+            let options = projects.len() + 1;
+            for code in 0..options.pow(groups.len() as u32) {
+                let mut rest = code;
+                let mut matching = Vec::new();
+                for g in &groups {
+                    let choice = rest % options;
+                    rest /= options;
+                    if let Some(p) = projects.get(choice) {
+                        matching.push(assign(&g.name, &p.company, &p.id));
+                    }
+                }
+                if verify_stability(&groups, &companies, &projects, &matching).stable {
+                    brute_force += 1;
+                }
+            }
+
+            let lattice = stable_lattice(&groups, &companies, &projects, 1000);
+            assert_eq!(lattice.matchings.len(), brute_force, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn lattice_contains_both_proposer_optimal_matchings() {
+        // Kwadrat łaciński: trzy stabilne skojarzenia
+        let groups = vec![
+            group("g1", &["c1", "c2", "c3"]),
+            group("g2", &["c2", "c3", "c1"]),
+            group("g3", &["c3", "c1", "c2"]),
+        ];
+        let companies = vec![
+            company("c1", &["g2", "g3", "g1"]),
+            company("c2", &["g3", "g1", "g2"]),
+            company("c3", &["g1", "g2", "g3"]),
+        ];
+
+        let lattice = stable_lattice(&groups, &companies, &[], 10);
+        assert_eq!(lattice.matchings.len(), 3);
+
+        let as_pairs = |results: &[MatchResult]| results.iter()
+            .map(|m| (m.group.clone(), m.company.clone()))
+            .collect::<Vec<_>>();
+        for proposer in [Proposer::Groups, Proposer::Companies] {
+            let matching = stable_matching(&groups, &companies, &[], proposer);
+            assert!(lattice.matchings.iter().any(|m| as_pairs(&m.results) == as_pairs(&matching)));
+        }
+
+        let capped = stable_lattice(&groups, &companies, &[], 2);
+        assert_eq!(capped.matchings.len(), 2);
+        assert!(capped.truncated);
+    }
+}
@@ -0,0 +1,426 @@
+use super::Market;
+use super::lattice::SlotMarket;
+use crate::models::{Group, Company, Project, MatchResult, MatchRun, RematchReport, GroupMove};
+use crate::history::{restore, group_snapshot, company_snapshot, list_changes, describe_change};
+use std::collections::VecDeque;
+
+struct Edge {
+    to: usize,
+    capacity: i64,
+}
+
+// Maksymalny przepływ (Edmonds-Karp) na małej sieci rotacji
+struct FlowNetwork {
+    edges: Vec<Edge>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl FlowNetwork {
+    fn new(nodes: usize) -> Self {
+        FlowNetwork {
+            edges: Vec::new(),
+            adjacency: vec![Vec::new(); nodes],
+        }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, capacity: i64) {
+        self.adjacency[from].push(self.edges.len());
+        self.edges.push(Edge { to, capacity });
+        self.adjacency[to].push(self.edges.len());
+        self.edges.push(Edge { to: from, capacity: 0 });
+    }
+
+    // Krawędź, którą BFS dotarł do każdego wierzchołka osiągalnego ze źródła w sieci residualnej
+    fn residual_bfs(&self, source: usize) -> Vec<Option<usize>> {
+        let mut via = vec![None; self.adjacency.len()];
+        let mut visited = vec![false; self.adjacency.len()];
+        let mut queue = VecDeque::from([source]);
+        visited[source] = true;
+
+        while let Some(node) = queue.pop_front() {
+            for &e_idx in &self.adjacency[node] {
+                let edge = &self.edges[e_idx];
+                if edge.capacity > 0 && !visited[edge.to] {
+                    visited[edge.to] = true;
+                    via[edge.to] = Some(e_idx);
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+        via
+    }
+
+    fn max_flow(&mut self, source: usize, sink: usize) {
+        loop {
+            let via = self.residual_bfs(source);
+            if via[sink].is_none() {
+                return;
+            }
+
+            let mut path = Vec::new();
+            let mut node = sink;
+            while node != source {
+                let e_idx = via[node].unwrap();
+                path.push(e_idx);
+                node = self.edges[e_idx ^ 1].to;
+            }
+
+            let bottleneck = path.iter().map(|&e| self.edges[e].capacity).min().unwrap();
+            for e_idx in path {
+                self.edges[e_idx].capacity -= bottleneck;
+                self.edges[e_idx ^ 1].capacity += bottleneck;
+            }
+        }
+    }
+}
+
+// Zbiór rotacji zamknięty na poprzedników o największym łącznym zysku (minimalny przekrój)
+pub(super) fn max_weight_closure(profit: &[i64], predecessors: &[Vec<usize>]) -> Vec<bool> {
+    let source = profit.len();
+    let sink = profit.len() + 1;
+    let infinite: i64 = profit.iter().map(|p| p.abs()).sum::<i64>() + 1;
+    let mut network = FlowNetwork::new(profit.len() + 2);
+
+    for (r_idx, &p) in profit.iter().enumerate() {
+        if p > 0 {
+            network.add_edge(source, r_idx, p);
+        } else if p < 0 {
+            network.add_edge(r_idx, sink, -p);
+        }
+        for &pred in &predecessors[r_idx] {
+            network.add_edge(r_idx, pred, infinite);
+        }
+    }
+
+    network.max_flow(source, sink);
+
+    let via = network.residual_bfs(source);
+    (0..profit.len()).map(|r_idx| via[r_idx].is_some()).collect()
+}
+
+// Dodaje rotację razem ze wszystkimi jej poprzednikami
+pub(super) fn close(chosen: &mut [bool], predecessors: &[Vec<usize>], r_idx: usize) {
+    let mut stack = vec![r_idx];
+    while let Some(r) = stack.pop() {
+        if !chosen[r] {
+            chosen[r] = true;
+            stack.extend(&predecessors[r]);
+        }
+    }
+}
+
+// Skojarzenie stabilne o najmniejszej sumie pozycji obu stron
+pub fn egalitarian_matching(groups: &[Group], companies: &[Company], projects: &[Project]) -> Vec<MatchResult> {
+    if groups.is_empty() || companies.is_empty() {
+        return vec![];
+    }
+
+    let market = Market::new(groups, companies, projects);
+    let slots = SlotMarket::new(&market);
+    let poset = slots.rotation_poset();
+
+    let cost = |g_idx: usize, s_idx: usize| {
+        let p_idx = slots.slot_project[s_idx];
+        (market.group_rank(g_idx, p_idx).unwrap() + market.company_rank(p_idx, g_idx).unwrap()) as i64
+    };
+
+    // Zysk rotacji = o ile spada łączna suma pozycji po jej eliminacji
+    let profit: Vec<i64> = poset.rotations.iter()
+        .map(|rotation| (0..rotation.len())
+            .map(|i| {
+                let (g_idx, s_idx) = rotation[i];
+                let (_, s_next) = rotation[(i + 1) % rotation.len()];
+                cost(g_idx, s_idx) - cost(g_idx, s_next)
+            })
+            .sum())
+        .collect();
+
+    let chosen = max_weight_closure(&profit, &poset.predecessors);
+    let assignment = slots.project_assignment(&slots.apply(&poset, &chosen));
+    market.results(&assignment)
+}
+
+// Skojarzenie stabilne minimalizujące najgorszą pozycję, jaką ktokolwiek dostał (Gusfield)
+pub fn minimum_regret_matching(groups: &[Group], companies: &[Company], projects: &[Project]) -> Vec<MatchResult> {
+    if groups.is_empty() || companies.is_empty() {
+        return vec![];
+    }
+
+    let market = Market::new(groups, companies, projects);
+    let slots = SlotMarket::new(&market);
+    let poset = slots.rotation_poset();
+
+    let mut chosen = vec![false; poset.rotations.len()];
+    let mut matching = poset.start.clone();
+    let mut best: Option<(usize, Vec<Option<usize>>)> = None;
+
+    loop {
+        let group_ranks: Vec<usize> = matching.iter()
+            .enumerate()
+            .filter_map(|(g_idx, s_idx)| {
+                market.group_rank(g_idx, slots.slot_project[(*s_idx)?]).map(|r| r + 1)
+            })
+            .collect();
+        let slot_ranks: Vec<(usize, usize)> = slots.slot_partners(&matching).iter()
+            .enumerate()
+            .filter_map(|(s_idx, g_idx)| {
+                market.company_rank(slots.slot_project[s_idx], (*g_idx)?).map(|r| (s_idx, r + 1))
+            })
+            .collect();
+
+        let regret = group_ranks.iter()
+            .copied()
+            .chain(slot_ranks.iter().map(|&(_, r)| r))
+            .max()
+            .unwrap_or(0);
+
+        if best.as_ref().is_none_or(|(best_regret, _)| regret < *best_regret) {
+            best = Some((regret, matching.clone()));
+        }
+
+        // Grupy tylko tracą w dalszej części kraty, więc ich regret jest już nieusuwalny
+        if regret == 0 || group_ranks.contains(&regret) {
+            break;
+        }
+
+        // Każde miejsce z najgorszą pozycją musi dostać lepszą grupę
+        let next_moves: Option<Vec<usize>> = slot_ranks.iter()
+            .filter(|&&(_, r)| r == regret)
+            .map(|&(s_idx, _)| poset.slot_moves[s_idx].iter()
+                .map(|&(r_idx, _)| r_idx)
+                .find(|&r_idx| !chosen[r_idx]))
+            .collect();
+
+        let Some(next_moves) = next_moves else {
+            break;
+        };
+        for r_idx in next_moves {
+            close(&mut chosen, &poset.predecessors, r_idx);
+        }
+        matching = slots.apply(&poset, &chosen);
+    }
+
+    let (_, matching) = best.unwrap();
+    market.results(&slots.project_assignment(&matching))
+}
+
+// Skojarzenie stabilne zachowujące jak najwięcej par z `baseline` (np. opublikowanego wyniku)
+pub fn minimal_disruption_matching(groups: &[Group], companies: &[Company], projects: &[Project], baseline: &[MatchResult]) -> Vec<MatchResult> {
+    if groups.is_empty() || companies.is_empty() {
+        return vec![];
+    }
+
+    let market = Market::new(groups, companies, projects);
+    let slots = SlotMarket::new(&market);
+    let poset = slots.rotation_poset();
+
+    let kept = |g_idx: usize, s_idx: usize| {
+        let project = &market.projects[slots.slot_project[s_idx]].id;
+        baseline.iter().any(|m| m.group == groups[g_idx].name && &m.project == project) as i64
+    };
+
+    // Zysk rotacji = ile par z bazowego przydziału przybywa po jej eliminacji
+    let profit: Vec<i64> = poset.rotations.iter()
+        .map(|rotation| (0..rotation.len())
+            .map(|i| {
+                let (g_idx, s_idx) = rotation[i];
+                let (_, s_next) = rotation[(i + 1) % rotation.len()];
+                kept(g_idx, s_next) - kept(g_idx, s_idx)
+            })
+            .sum())
+        .collect();
+
+    let chosen = max_weight_closure(&profit, &poset.predecessors);
+    let assignment = slots.project_assignment(&slots.apply(&poset, &chosen));
+    market.results(&assignment)
+}
+
+// Pary zachowane i zmienione względem przebiegu bazowego; przyczyny zmian wynikają z porównania list
+// z danymi wejściowymi przebiegu bazowego
+pub fn disruption_report(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    base: &MatchRun,
+    results: &[MatchResult],
+) -> RematchReport {
+    let (base_groups, base_companies) = restore(&base.input);
+    let snapshots = |groups: &[Group]| groups.iter().map(group_snapshot).collect::<Vec<_>>();
+    let company_snapshots = |companies: &[Company]| companies.iter().map(company_snapshot).collect::<Vec<_>>();
+    let mut changes = list_changes("group", &snapshots(&base_groups), &snapshots(groups));
+    changes.extend(list_changes("company", &company_snapshots(&base_companies), &company_snapshots(companies)));
+    let change = |side: &str, name: &str| changes.iter().find(|c| c.side == side && c.name == name);
+
+    let capacity = |list: &[Project], project: &str| list.iter().find(|p| p.id == project).map(|p| p.capacity);
+
+    let mut names: Vec<&String> = base.results.iter().chain(results).map(|m| &m.group).collect();
+    names.sort();
+    names.dedup();
+
+    let pair = |list: &[MatchResult], group: &str| list.iter()
+        .find(|m| m.group == group)
+        .map(|m| (m.project.clone(), m.company.clone()));
+
+    let mut kept = Vec::new();
+    let mut changed = Vec::new();
+    for name in names {
+        let before = pair(&base.results, name);
+        let after = pair(results, name);
+        if before.as_ref().map(|b| &b.0) == after.as_ref().map(|a| &a.0) {
+            kept.extend(results.iter().find(|m| &m.group == name).cloned());
+            continue;
+        }
+
+        let mut reasons = Vec::new();
+        if !groups.iter().any(|g| &g.name == name) {
+            reasons.push("group withdrew".to_string());
+        } else if !base.input.groups.iter().any(|g| &g.name == name) {
+            reasons.push("group registered after the base run".to_string());
+        } else {
+            reasons.extend(change("group", name).map(describe_change));
+            let mut touched: Vec<&String> = before.iter().chain(after.iter()).map(|(_, c)| c).collect();
+            touched.dedup();
+            reasons.extend(touched.into_iter().filter_map(|c| change("company", c)).map(describe_change));
+            for (project, _) in before.iter().chain(after.iter()) {
+                let (old, new) = (capacity(&base.input.projects, project), capacity(projects, project));
+                if old != new {
+                    reasons.push(format!("project '{}' capacity changed: {:?} -> {:?}", project, old, new));
+                }
+            }
+        }
+
+        if reasons.is_empty() {
+            reasons.push(match (&before, &after) {
+                (None, _) => "group was unmatched in the base run".into(),
+                (_, None) => "no stable matching keeps a seat for the group".into(),
+                _ if changes.is_empty() => "moved so the new matching stays stable".into(),
+                _ => {
+                    let others: Vec<String> = changes.iter().map(|c| format!("{} '{}'", c.side, c.name)).collect();
+                    format!("knock-on effect of changed lists: {}", others.join(", "))
+                }
+            });
+        }
+        changed.push(GroupMove {
+            group: name.clone(),
+            from_project: before.map(|(p, _)| p),
+            to_project: after.map(|(p, _)| p),
+            reasons,
+        });
+    }
+
+    RematchReport {
+        base_run: base.id.clone(),
+        kept,
+        changed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{stable_matching, stable_lattice, verify_stability};
+    use crate::matching::testing::{group, company, project, random_instance, dense_instance};
+    use crate::models::{Proposer, InputSnapshot};
+
+    // Suma i maksimum pozycji obu stron, z pominięciem przypisań awaryjnych
+    fn rank_profile(groups: &[Group], companies: &[Company], results: &[MatchResult]) -> (usize, usize) {
+        let ranks: Vec<usize> = results.iter()
+            .filter_map(|m| {
+                let g = groups.iter().find(|g| g.name == m.group)?;
+                let c = companies.iter().find(|c| c.name == m.company)?;
+                let g_rank = g.preferences.iter().position(|p| *p == m.project || *p == m.company)?;
+                let c_rank = c.preferences.iter().position(|n| *n == m.group)?;
+                Some([g_rank + 1, c_rank + 1])
+            })
+            .flatten()
+            .collect();
+        (ranks.iter().sum(), ranks.iter().copied().max().unwrap_or(0))
+    }
+
+    #[test]
+    fn egalitarian_and_minimum_regret_are_optimal_in_the_lattice() {
+        for seed in 1..150 {
+            let (groups, companies, projects) = if seed % 2 == 0 { dense_instance(seed) } else { random_instance(seed) };
+            let lattice = stable_lattice(&groups, &companies, &projects, 10_000);
+            let profiles: Vec<(usize, usize)> = lattice.matchings.iter()
+                .map(|m| rank_profile(&groups, &companies, &m.results))
+                .collect();
+
+            let egalitarian = egalitarian_matching(&groups, &companies, &projects);
+            let report = verify_stability(&groups, &companies, &projects, &egalitarian);
+            assert!(report.blocking_pairs.is_empty(), "seed {}: {:?}", seed, report);
+            assert_eq!(
+                rank_profile(&groups, &companies, &egalitarian).0,
+                profiles.iter().map(|p| p.0).min().unwrap(),
+                "seed {}", seed
+            );
+
+            let minimum_regret = minimum_regret_matching(&groups, &companies, &projects);
+            let report = verify_stability(&groups, &companies, &projects, &minimum_regret);
+            assert!(report.blocking_pairs.is_empty(), "seed {}: {:?}", seed, report);
+            assert_eq!(
+                rank_profile(&groups, &companies, &minimum_regret).1,
+                profiles.iter().map(|p| p.1).min().unwrap(),
+                "seed {}", seed
+            );
+        }
+    }
+
+    #[test]
+    fn minimal_disruption_keeps_the_most_pairs_in_the_lattice() {
+        let common = |a: &[MatchResult], b: &[MatchResult]| a.iter()
+            .filter(|m| b.iter().any(|n| n.group == m.group && n.project == m.project))
+            .count();
+
+        for seed in 1..150 {
+            let (groups, companies, projects) = if seed % 2 == 0 { dense_instance(seed) } else { random_instance(seed) };
+
+            // Bazą jest wynik sprzed późnej zmiany: jedna grupa miała odwróconą listę
+            let mut before = groups.clone();
+            before[0].preferences.reverse();
+            let baseline = stable_matching(&before, &companies, &projects, Proposer::Companies);
+
+            let rematch = minimal_disruption_matching(&groups, &companies, &projects, &baseline);
+            let report = verify_stability(&groups, &companies, &projects, &rematch);
+            assert!(report.blocking_pairs.is_empty(), "seed {}: {:?}", seed, report);
+
+            let lattice = stable_lattice(&groups, &companies, &projects, 10_000);
+            let best = lattice.matchings.iter().map(|m| common(&m.results, &baseline)).max().unwrap();
+            assert_eq!(common(&rematch, &baseline), best, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn disruption_report_lists_kept_pairs_and_blames_changed_lists() {
+        let groups = vec![group("g1", &["c1", "c2"]), group("g2", &["c1", "c2"]), group("g3", &["c3"])];
+        let mut companies = vec![company("c1", &["g1", "g2"]), company("c2", &["g1", "g2"]), company("c3", &["g3"])];
+        let projects = vec![project("c1", "c1", 1), project("c2", "c2", 1), project("c3", "c3", 1)];
+
+        let base = MatchRun {
+            id: "base".into(),
+            created_at: 0,
+            algorithm: Default::default(),
+            params: Default::default(),
+            seed: None,
+            input_hash: String::new(),
+            input: InputSnapshot {
+                groups: groups.iter().map(group_snapshot).collect(),
+                companies: companies.iter().map(company_snapshot).collect(),
+                projects: projects.clone(),
+                ..Default::default()
+            },
+            results: stable_matching(&groups, &companies, &projects, Proposer::Groups),
+            unmatched: vec![],
+        };
+
+        companies[0].preferences = vec!["g2".into(), "g1".into()];
+        let results = minimal_disruption_matching(&groups, &companies, &projects, &base.results);
+        let report = disruption_report(&groups, &companies, &projects, &base, &results);
+
+        assert_eq!(report.kept.iter().map(|m| (m.group.as_str(), m.project.as_str())).collect::<Vec<_>>(), [("g3", "c3")]);
+        assert_eq!(report.changed.len(), 2);
+        for change in &report.changed {
+            assert_eq!(change.reasons, ["company 'c1' changed its preferences"], "{}", change.group);
+        }
+    }
+}
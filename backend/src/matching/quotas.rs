@@ -0,0 +1,243 @@
+use super::{Market, group_proposing, verify_stability};
+use crate::models::{Group, Company, Project, MatchResult, QuotaReport, QuotaRelaxation};
+use std::collections::VecDeque;
+
+struct CostEdge {
+    to: usize,
+    capacity: i64,
+    cost: i64,
+}
+
+// Przepływ o minimalnym koszcie (najkrótsze ścieżki Bellmana-Forda, koszty mogą być ujemne)
+struct CostNetwork {
+    edges: Vec<CostEdge>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl CostNetwork {
+    fn new(nodes: usize) -> Self {
+        CostNetwork {
+            edges: Vec::new(),
+            adjacency: vec![Vec::new(); nodes],
+        }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, capacity: i64, cost: i64) -> usize {
+        let e_idx = self.edges.len();
+        self.adjacency[from].push(e_idx);
+        self.edges.push(CostEdge { to, capacity, cost });
+        self.adjacency[to].push(e_idx + 1);
+        self.edges.push(CostEdge { to: from, capacity: 0, cost: -cost });
+        e_idx
+    }
+
+    fn flow(&self, e_idx: usize) -> i64 {
+        self.edges[e_idx ^ 1].capacity
+    }
+
+    fn shortest_path(&self, source: usize, sink: usize) -> Option<(i64, Vec<usize>)> {
+        let nodes = self.adjacency.len();
+        let mut distance = vec![i64::MAX; nodes];
+        let mut via = vec![None; nodes];
+        let mut queued = vec![false; nodes];
+        let mut queue = VecDeque::from([source]);
+        distance[source] = 0;
+
+        while let Some(node) = queue.pop_front() {
+            queued[node] = false;
+            for &e_idx in &self.adjacency[node] {
+                let edge = &self.edges[e_idx];
+                let next = distance[node] + edge.cost;
+                if edge.capacity > 0 && next < distance[edge.to] {
+                    distance[edge.to] = next;
+                    via[edge.to] = Some(e_idx);
+                    if !queued[edge.to] {
+                        queued[edge.to] = true;
+                        queue.push_back(edge.to);
+                    }
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut node = sink;
+        while node != source {
+            let e_idx = via[node]?;
+            path.push(e_idx);
+            node = self.edges[e_idx ^ 1].to;
+        }
+        Some((distance[sink], path))
+    }
+
+    // Powiększa przepływ, dopóki to obniża koszt
+    fn min_cost_flow(&mut self, source: usize, sink: usize) {
+        while let Some((cost, path)) = self.shortest_path(source, sink) {
+            if cost >= 0 {
+                return;
+            }
+            let bottleneck = path.iter().map(|&e| self.edges[e].capacity).min().unwrap();
+            for e_idx in path {
+                self.edges[e_idx].capacity -= bottleneck;
+                self.edges[e_idx ^ 1].capacity += bottleneck;
+            }
+        }
+    }
+}
+
+// Przydział spełniający dolne i górne limity, jeśli się da. Kolejność celów: jak najwięcej miejsc
+// obowiązkowych, jak najwięcej par ze skojarzenia stabilnego, jak najwięcej grup z parą, najniższe pozycje.
+// Gdy limitów nie da się spełnić, raport podaje najmniejsze łączne obniżenie minimów.
+pub fn lower_quota_matching(groups: &[Group], companies: &[Company], projects: &[Project]) -> (Vec<MatchResult>, QuotaReport) {
+    let market = Market::new(groups, companies, projects);
+    let stable = group_proposing(&market);
+    let group_count = groups.len() as i64;
+
+    // Pary akceptowalne dla obu stron z łączną pozycją (poziomy remisów po obu stronach)
+    let mut pairs: Vec<(usize, usize, i64)> = Vec::new();
+    for (g_idx, prefs) in market.group_prefs.iter().enumerate() {
+        for &p_idx in prefs {
+            if let (Some(g_tier), Some(c_tier)) = (market.group_tier(g_idx, p_idx), market.company_tier(p_idx, g_idx)) {
+                pairs.push((g_idx, p_idx, (g_tier + c_tier) as i64));
+            }
+        }
+    }
+
+    // Wagi dobrane tak, by każdy cel ważył więcej niż suma wszystkich niższych
+    let max_rank = pairs.iter().map(|&(_, _, r)| r).max().unwrap_or(0);
+    let match_bonus = group_count * (max_rank + 1) + 1;
+    let keep_bonus = (group_count + 1) * match_bonus;
+    let quota_bonus = (group_count + 1) * (keep_bonus + match_bonus);
+
+    let source = 0;
+    let group_node = |g_idx: usize| 1 + g_idx;
+    let project_node = |p_idx: usize| 1 + groups.len() + p_idx;
+    let company_node = |c_idx: usize| 1 + groups.len() + market.projects.len() + c_idx;
+    let sink = 1 + groups.len() + market.projects.len() + companies.len();
+    let mut network = CostNetwork::new(sink + 1);
+
+    for g_idx in 0..groups.len() {
+        network.add_edge(source, group_node(g_idx), 1, 0);
+    }
+    let pair_edges: Vec<(usize, usize, usize)> = pairs.iter()
+        .map(|&(g_idx, p_idx, rank)| {
+            let kept = if stable[g_idx] == Some(p_idx) { keep_bonus } else { 0 };
+            let e_idx = network.add_edge(group_node(g_idx), project_node(p_idx), 1, rank - match_bonus - kept);
+            (g_idx, p_idx, e_idx)
+        })
+        .collect();
+
+    let mut project_minimums = Vec::new();
+    for (p_idx, project) in market.projects.iter().enumerate() {
+        let minimum = project.min_capacity.min(project.capacity) as i64;
+        let c_node = company_node(market.project_company[p_idx]);
+        project_minimums.push(network.add_edge(project_node(p_idx), c_node, minimum, -quota_bonus));
+        network.add_edge(project_node(p_idx), c_node, project.capacity as i64 - minimum, 0);
+    }
+    let mut company_minimums = Vec::new();
+    for (c_idx, company) in companies.iter().enumerate() {
+        company_minimums.push(network.add_edge(company_node(c_idx), sink, company.min_groups as i64, -quota_bonus));
+        network.add_edge(company_node(c_idx), sink, group_count, 0);
+    }
+
+    network.min_cost_flow(source, sink);
+
+    let mut assignment = vec![None; groups.len()];
+    for &(g_idx, p_idx, e_idx) in &pair_edges {
+        if network.flow(e_idx) > 0 {
+            assignment[g_idx] = Some(p_idx);
+        }
+    }
+
+    let mut relaxations = Vec::new();
+    for (p_idx, project) in market.projects.iter().enumerate() {
+        let achieved = network.flow(project_minimums[p_idx]) as usize;
+        if achieved < project.min_capacity {
+            relaxations.push(QuotaRelaxation {
+                company: project.company.clone(),
+                project: Some(project.id.clone()),
+                minimum: project.min_capacity,
+                achievable: achieved,
+            });
+        }
+    }
+    for (c_idx, company) in companies.iter().enumerate() {
+        let achieved = network.flow(company_minimums[c_idx]) as usize;
+        if achieved < company.min_groups {
+            relaxations.push(QuotaRelaxation {
+                company: company.name.clone(),
+                project: None,
+                minimum: company.min_groups,
+                achievable: achieved,
+            });
+        }
+    }
+
+    let results = market.results(&assignment);
+    let report = QuotaReport {
+        feasible: relaxations.is_empty(),
+        relaxations,
+        blocking_pairs: verify_stability(groups, companies, projects, &results).blocking_pairs.len(),
+    };
+    (results, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::testing::{tied_instance, all_matchings};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    // Ile jednostek minimów (projektów i firm) spełnia dany przydział
+    fn satisfied(market: &Market, assignment: &[Option<usize>]) -> usize {
+        let mut held = vec![0; market.projects.len()];
+        let mut per_company = vec![0; market.companies.len()];
+        for &p_idx in assignment.iter().flatten() {
+            held[p_idx] += 1;
+            per_company[market.project_company[p_idx]] += 1;
+        }
+        let projects: usize = market.projects.iter().zip(&held).map(|(p, &h)| p.min_capacity.min(h)).sum();
+        let companies: usize = market.companies.iter().zip(&per_company).map(|(c, &h)| c.min_groups.min(h)).sum();
+        projects + companies
+    }
+
+    #[test]
+    fn meets_quotas_whenever_possible_and_reports_minimal_relaxations() {
+        for seed in 0..300 {
+            let (groups, mut companies, mut projects) = tied_instance(seed);
+            let mut rng = ChaCha8Rng::seed_from_u64(seed + 1000);
+            for c in &mut companies {
+                c.min_groups = rng.gen_range(0..=2);
+            }
+            for p in &mut projects {
+                p.min_capacity = rng.gen_range(0..=p.capacity);
+            }
+
+            let market = Market::new(&groups, &companies, &projects);
+            let best = all_matchings(&market).iter().map(|m| satisfied(&market, m)).max().unwrap();
+            let required: usize = companies.iter().map(|c| c.min_groups).sum::<usize>()
+                + projects.iter().map(|p| p.min_capacity).sum::<usize>();
+
+            let (results, report) = lower_quota_matching(&groups, &companies, &projects);
+            let assignment: Vec<Option<usize>> = groups.iter()
+                .map(|g| results.iter()
+                    .find(|m| m.group == g.name)
+                    .and_then(|m| market.projects.iter().position(|p| p.id == m.project)))
+                .collect();
+
+            assert_eq!(satisfied(&market, &assignment), best, "seed {}", seed);
+            assert_eq!(report.feasible, best == required, "seed {}", seed);
+            let relaxed: usize = report.relaxations.iter().map(|r| r.minimum - r.achievable).sum();
+            assert_eq!(relaxed, required - best, "seed {}", seed);
+            for p in &projects {
+                assert!(results.iter().filter(|m| m.project == p.id).count() <= p.capacity);
+            }
+
+            // Gdy skojarzenie stabilne spełnia limity, zostaje bez zmian
+            let stable = group_proposing(&market);
+            if satisfied(&market, &stable) == required {
+                assert_eq!(assignment, stable, "seed {}", seed);
+            }
+        }
+    }
+}
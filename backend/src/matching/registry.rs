@@ -0,0 +1,558 @@
+use super::{
+    stable_matching, egalitarian_matching, minimum_regret_matching,
+    rank_maximal_matching, min_cost_matching, rank_profile, place_unmatched,
+    break_ties, lottery_ties, super_stable_matching, strongly_stable_matching,
+    random_serial_dictatorship, top_trading_cycles, minimal_disruption_matching, disruption_report,
+    annotate_results, verify_stability, eligible_lists, lower_quota_matching, track_reserve_matching,
+};
+use super::constraints::apply_constraints;
+use crate::models::{
+    Group, Company, Project, MatchParams, MatchResult, MatchResponse, Proposer, TieBreak, TieReport,
+    UnmatchedPolicy, MatchRun, PairConstraints, Application, ConstraintReport, QuotaReport, ReservationReport, Algorithm, AlgorithmParameter, AlgorithmInfo, AlgorithmCatalog,
+};
+
+pub struct MatchInput<'a> {
+    pub groups: &'a [Group],
+    pub companies: &'a [Company],
+    pub projects: &'a [Project],
+    // Wyniki przebiegu bazowego (pusty, gdy go nie ma)
+    pub baseline: &'a [MatchResult],
+}
+
+// Skojarzenie plus diagnostyka, którą algorytm chce pokazać obok wyników
+#[derive(Default)]
+pub struct MatchOutcome {
+    pub results: Vec<MatchResult>,
+    pub tie_report: Option<TieReport>,
+    pub quota_report: Option<QuotaReport>,
+    pub reservation_report: Option<ReservationReport>,
+}
+
+// Nowy algorytm = wariant `Algorithm` + implementacja tej cechy dopisana do REGISTRY
+pub trait MatchingAlgorithm: Sync {
+    fn algorithm(&self) -> Algorithm;
+    fn description(&self) -> &'static str;
+
+    fn parameters(&self) -> Vec<AlgorithmParameter> {
+        Vec::new()
+    }
+
+    // Czy obok wyniku pokazać profil rang w porównaniu z Gale-Shapleyem
+    fn compare_with_gale_shapley(&self) -> bool {
+        false
+    }
+
+    // Algorytm losowy dostaje w `params.seed` zawsze ustalony seed
+    fn uses_seed(&self) -> bool {
+        false
+    }
+
+    // Algorytm liczy się względem przebiegu bazowego; odpowiedź dostaje raport zmian
+    fn uses_baseline(&self) -> bool {
+        false
+    }
+
+    // Wynik jest zawsze stabilny względem list obu stron (sprawdzane testem dla całego REGISTRY)
+    fn stable(&self) -> bool {
+        false
+    }
+
+    fn run(&self, input: &MatchInput, params: &MatchParams) -> MatchOutcome;
+}
+
+fn parameter(name: &str, description: &str, values: &[&str], default: Option<&str>) -> AlgorithmParameter {
+    AlgorithmParameter {
+        name: name.into(),
+        description: description.into(),
+        values: values.iter().map(|v| v.to_string()).collect(),
+        default: default.map(String::from),
+    }
+}
+
+struct GaleShapley;
+struct Egalitarian;
+struct MinimumRegret;
+struct RankMaximal;
+struct MinCost;
+struct SerialDictatorship;
+struct TopTradingCycles;
+struct MinimalDisruption;
+struct LowerQuotas;
+struct TrackReserves;
+
+impl MatchingAlgorithm for GaleShapley {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::GaleShapley
+    }
+
+    fn description(&self) -> &'static str {
+        "Deferred acceptance; the proposing side gets its best stable matching"
+    }
+
+    fn parameters(&self) -> Vec<AlgorithmParameter> {
+        vec![parameter("proposer", "Side that proposes", &["groups", "companies"], Some("groups"))]
+    }
+
+    fn stable(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, params: &MatchParams) -> MatchOutcome {
+        MatchOutcome {
+            results: stable_matching(input.groups, input.companies, input.projects, params.proposer),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for Egalitarian {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::Egalitarian
+    }
+
+    fn description(&self) -> &'static str {
+        "Stable matching with the smallest total of both sides' ranks"
+    }
+
+    fn stable(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, _params: &MatchParams) -> MatchOutcome {
+        MatchOutcome {
+            results: egalitarian_matching(input.groups, input.companies, input.projects),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for MinimumRegret {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::MinimumRegret
+    }
+
+    fn description(&self) -> &'static str {
+        "Stable matching minimising the worst rank anyone receives"
+    }
+
+    fn stable(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, _params: &MatchParams) -> MatchOutcome {
+        MatchOutcome {
+            results: minimum_regret_matching(input.groups, input.companies, input.projects),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for RankMaximal {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::RankMaximal
+    }
+
+    fn description(&self) -> &'static str {
+        "Maximises groups at first choice, then second, ...; may be unstable"
+    }
+
+    fn compare_with_gale_shapley(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, _params: &MatchParams) -> MatchOutcome {
+        MatchOutcome {
+            results: rank_maximal_matching(input.groups, input.companies, input.projects),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for MinCost {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::MinCost
+    }
+
+    fn description(&self) -> &'static str {
+        "Maximum matching with the smallest total of both sides' ranks; may be unstable"
+    }
+
+    fn compare_with_gale_shapley(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, _params: &MatchParams) -> MatchOutcome {
+        MatchOutcome {
+            results: min_cost_matching(input.groups, input.companies, input.projects),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for SerialDictatorship {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::SerialDictatorship
+    }
+
+    fn description(&self) -> &'static str {
+        "Groups pick their best free project in a random (seeded) order; company rankings are ignored"
+    }
+
+    fn uses_seed(&self) -> bool {
+        true
+    }
+
+    fn compare_with_gale_shapley(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, params: &MatchParams) -> MatchOutcome {
+        MatchOutcome {
+            results: random_serial_dictatorship(input.groups, input.companies, input.projects, params.seed.unwrap_or_default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for TopTradingCycles {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::TopTradingCycles
+    }
+
+    fn description(&self) -> &'static str {
+        "Top Trading Cycles over company priorities; Pareto-efficient and strategy-proof for groups"
+    }
+
+    fn parameters(&self) -> Vec<AlgorithmParameter> {
+        vec![parameter(
+            "priority",
+            "Company priority over groups: its own list (ties by priority score) or the priority score alone",
+            &["company", "score"],
+            Some("company"),
+        )]
+    }
+
+    fn compare_with_gale_shapley(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, params: &MatchParams) -> MatchOutcome {
+        MatchOutcome {
+            results: top_trading_cycles(input.groups, input.companies, input.projects, params.priority),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for MinimalDisruption {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::MinimalDisruption
+    }
+
+    fn description(&self) -> &'static str {
+        "Stable matching that keeps as many pairs of the base (published) run as possible"
+    }
+
+    fn parameters(&self) -> Vec<AlgorithmParameter> {
+        vec![parameter("base_run", "Run to stay close to; defaults to the published run", &[], None)]
+    }
+
+    fn uses_baseline(&self) -> bool {
+        true
+    }
+
+    fn stable(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, _params: &MatchParams) -> MatchOutcome {
+        MatchOutcome {
+            results: minimal_disruption_matching(input.groups, input.companies, input.projects, input.baseline),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for LowerQuotas {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::LowerQuotas
+    }
+
+    fn description(&self) -> &'static str {
+        "Meets companies' min_groups and projects' min_capacity where feasible, staying as close to the stable matching as possible"
+    }
+
+    fn compare_with_gale_shapley(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, _params: &MatchParams) -> MatchOutcome {
+        let (results, report) = lower_quota_matching(input.groups, input.companies, input.projects);
+        MatchOutcome {
+            results,
+            quota_report: Some(report),
+            ..Default::default()
+        }
+    }
+}
+
+impl MatchingAlgorithm for TrackReserves {
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::TrackReserves
+    }
+
+    fn description(&self) -> &'static str {
+        "Gale-Shapley where companies fill seats reserved for a track with that track's best groups first"
+    }
+
+    fn compare_with_gale_shapley(&self) -> bool {
+        true
+    }
+
+    fn run(&self, input: &MatchInput, _params: &MatchParams) -> MatchOutcome {
+        let (results, report) = track_reserve_matching(input.groups, input.companies, input.projects);
+        MatchOutcome {
+            results,
+            reservation_report: Some(report),
+            ..Default::default()
+        }
+    }
+}
+
+static REGISTRY: &[&dyn MatchingAlgorithm] = &[
+    &GaleShapley,
+    &Egalitarian,
+    &MinimumRegret,
+    &RankMaximal,
+    &MinCost,
+    &SerialDictatorship,
+    &TopTradingCycles,
+    &MinimalDisruption,
+    &LowerQuotas,
+    &TrackReserves,
+];
+
+fn find_algorithm(algorithm: Algorithm) -> &'static dyn MatchingAlgorithm {
+    REGISTRY.iter()
+        .copied()
+        .find(|a| a.algorithm() == algorithm)
+        .expect("every Algorithm variant is registered")
+}
+
+// Parametry /match niezależne od wybranego algorytmu
+fn common_parameters() -> Vec<AlgorithmParameter> {
+    vec![
+        parameter(
+            "ties",
+            "Tie handling; lottery breaks company ties with one draw over all groups, \
+             super_stable and strongly_stable run their own group-proposing algorithm \
+             and reject any other algorithm or proposer",
+            &["list_order", "random", "lottery", "super_stable", "strongly_stable"],
+            Some("list_order"),
+        ),
+        parameter("seed", "Seed for every random step of the run; drawn and returned when omitted", &[], None),
+        parameter(
+            "unmatched",
+            "Policy for groups left without a match",
+            &["leave", "free_acceptable", "overflow", "random"],
+            Some("leave"),
+        ),
+        parameter("overflow_limit", "Extra seats per project under the overflow policy", &[], Some("1")),
+    ]
+}
+
+pub fn algorithm_catalog() -> AlgorithmCatalog {
+    AlgorithmCatalog {
+        algorithms: REGISTRY.iter()
+            .map(|a| AlgorithmInfo {
+                algorithm: a.algorithm(),
+                description: a.description().into(),
+                parameters: a.parameters(),
+                stable: a.stable(),
+            })
+            .collect(),
+        common_parameters: common_parameters(),
+    }
+}
+
+// super_stable i strongly_stable zastępują algorytm własnym (proponują grupy),
+// więc inny algorytm albo strona proponująca zostałyby po cichu pominięte
+pub fn conflicting_params(params: &MatchParams) -> bool {
+    matches!(params.ties, TieBreak::SuperStable | TieBreak::StronglyStable)
+        && (params.algorithm != Algorithm::GaleShapley || params.proposer != Proposer::Groups)
+}
+
+// Pełny przebieg /match: ograniczenia administratora, remisy, wybrany algorytm,
+// a na końcu polityka dla grup bez pary. `shortlist` to zgłoszenia w fazie zgłoszeń
+// (listy są już do nich ograniczone; tu ogranicza się też losowy przydział grup bez pary).
+// None, gdy parametry się wykluczają
+pub fn run_matching(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    params: &MatchParams,
+    baseline: Option<&MatchRun>,
+    constraints: &PairConstraints,
+    shortlist: Option<&[Application]>,
+) -> Option<MatchResponse> {
+    if conflicting_params(params) {
+        return None;
+    }
+    let algorithm = find_algorithm(params.algorithm);
+    let randomized = algorithm.uses_seed()
+        || matches!(params.ties, TieBreak::Random | TieBreak::Lottery)
+        || params.unmatched == UnmatchedPolicy::Random;
+    // Jeden seed na cały przebieg, żeby zapisany przebieg dało się odtworzyć
+    let seed = randomized.then(|| params.seed.unwrap_or_else(rand::random));
+    let params = &MatchParams { seed, ..params.clone() };
+    let mut market = apply_constraints(groups, companies, projects, constraints);
+    // Pary, w których grupa nie spełnia wymagań firmy lub projektu, są nieakceptowalne
+    (market.groups, market.companies) = eligible_lists(&market.groups, &market.companies, &market.projects);
+    let mut gale_shapley = None;
+
+    let mut outcome = match params.ties {
+        TieBreak::ListOrder | TieBreak::Random | TieBreak::Lottery => {
+            let (groups_used, companies_used) = match params.ties {
+                TieBreak::Random => break_ties(&market.groups, &market.companies, seed.unwrap_or_default()),
+                TieBreak::Lottery => (
+                    market.groups.clone(),
+                    lottery_ties(&market.groups, &market.companies, seed.unwrap_or_default()),
+                ),
+                _ => (market.groups.clone(), market.companies.clone()),
+            };
+            let input = MatchInput {
+                groups: &groups_used,
+                companies: &companies_used,
+                projects: &market.projects,
+                baseline: baseline.map_or(&[], |run| run.results.as_slice()),
+            };
+            if algorithm.compare_with_gale_shapley() {
+                gale_shapley = Some(stable_matching(&groups_used, &companies_used, &market.projects, params.proposer));
+            }
+            algorithm.run(&input, params)
+        }
+        TieBreak::SuperStable => {
+            let (results, report) = super_stable_matching(&market.groups, &market.companies, &market.projects);
+            MatchOutcome { results, tie_report: Some(report), ..Default::default() }
+        }
+        TieBreak::StronglyStable => {
+            let (results, report) = strongly_stable_matching(&market.groups, &market.companies, &market.projects);
+            MatchOutcome { results, tie_report: Some(report), ..Default::default() }
+        }
+    };
+    outcome.results.extend(market.pinned.iter().cloned());
+
+    // Profile liczone na oryginalnych listach, żeby remisy dawały tę samą pozycję
+    let comparison = gale_shapley.map(|mut gale_shapley| {
+        gale_shapley.extend(market.pinned.iter().cloned());
+        vec![
+            rank_profile(groups, companies, projects, params.algorithm, &outcome.results),
+            rank_profile(groups, companies, projects, Algorithm::GaleShapley, &gale_shapley),
+        ]
+    });
+
+    // Przypięte pary są już w wynikach, więc dosadzanie liczy miejsca na pełnych pojemnościach
+    let (mut results, unmatched) = place_unmatched(
+        &market.groups,
+        &market.companies,
+        projects,
+        outcome.results,
+        params,
+        &constraints.forbidden,
+        shortlist,
+    );
+    annotate_results(groups, companies, projects, &mut results);
+
+    let rematch = baseline
+        .filter(|_| algorithm.uses_baseline())
+        .map(|base| disruption_report(groups, companies, projects, base, &results));
+    let has_constraints = !constraints.pinned.is_empty() || !constraints.forbidden.is_empty();
+    let constraints = has_constraints.then(|| ConstraintReport {
+        applied: market.notes,
+        blocking_pairs: verify_stability(groups, companies, projects, &results).blocking_pairs,
+    });
+
+    Some(MatchResponse {
+        algorithm: params.algorithm,
+        proposer: params.proposer,
+        ties: params.ties,
+        seed,
+        run_id: None,
+        unmatched_policy: params.unmatched,
+        unmatched,
+        tie_report: outcome.tie_report,
+        comparison,
+        results,
+        published: false,
+        rematch,
+        constraints,
+        quotas: outcome.quota_report,
+        reservations: outcome.reservation_report,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::testing::tied_instance;
+
+    #[test]
+    fn every_algorithm_is_registered_once() {
+        let all = [
+            Algorithm::GaleShapley,
+            Algorithm::Egalitarian,
+            Algorithm::MinimumRegret,
+            Algorithm::RankMaximal,
+            Algorithm::MinCost,
+            Algorithm::SerialDictatorship,
+            Algorithm::TopTradingCycles,
+            Algorithm::MinimalDisruption,
+            Algorithm::LowerQuotas,
+            Algorithm::TrackReserves,
+        ];
+        for algorithm in all {
+            assert_eq!(REGISTRY.iter().filter(|a| a.algorithm() == algorithm).count(), 1);
+        }
+        assert_eq!(algorithm_catalog().algorithms.len(), all.len());
+    }
+
+    #[test]
+    fn stable_algorithms_return_stable_matchings() {
+        let stable: Vec<Algorithm> = REGISTRY.iter().filter(|a| a.stable()).map(|a| a.algorithm()).collect();
+        assert_eq!(stable.len(), 4);
+
+        for seed in 0..200 {
+            let (mut groups, mut companies, projects) = tied_instance(seed);
+            // Stabilność jest sprawdzana na ścisłych listach
+            groups.iter_mut().for_each(|g| g.tiers.clear());
+            companies.iter_mut().for_each(|c| c.tiers.clear());
+
+            for &algorithm in &stable {
+                for proposer in [Proposer::Groups, Proposer::Companies] {
+                    let params = MatchParams { algorithm, proposer, ..Default::default() };
+                    let response = run_matching(&groups, &companies, &projects, &params, None, &Default::default(), None).unwrap();
+                    let report = verify_stability(&groups, &companies, &projects, &response.results);
+                    assert!(report.stable, "{:?}, seed {}: {:?}", algorithm, seed, report);
+                }
+            }
+        }
+    }
+    #[test]
+    fn tie_modes_reject_another_algorithm_or_proposer() {
+        let (groups, companies, projects) = tied_instance(1);
+        let run = |algorithm, proposer, ties| {
+            let params = MatchParams { algorithm, proposer, ties, ..Default::default() };
+            run_matching(&groups, &companies, &projects, &params, None, &Default::default(), None)
+        };
+
+        for ties in [TieBreak::SuperStable, TieBreak::StronglyStable] {
+            let response = run(Algorithm::GaleShapley, Proposer::Groups, ties).unwrap();
+            assert_eq!((response.algorithm, response.ties), (Algorithm::GaleShapley, ties));
+            assert!(response.tie_report.is_some());
+
+            assert!(run(Algorithm::MinCost, Proposer::Groups, ties).is_none());
+            assert!(run(Algorithm::GaleShapley, Proposer::Companies, ties).is_none());
+        }
+        assert!(run(Algorithm::MinCost, Proposer::Companies, TieBreak::Random).is_some());
+    }
+}
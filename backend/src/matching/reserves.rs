@@ -0,0 +1,189 @@
+use super::{Market, verify_stability};
+use crate::models::{Group, Company, Project, MatchResult, ReservationReport, ReservedSeats};
+use std::collections::VecDeque;
+
+// Wybór firmy spośród trzymanych propozycji (grupa, projekt): najpierw miejsca zarezerwowane,
+// każde dla najlepszych grup danego kierunku, potem pozostałe miejsca według listy firmy.
+// Rezerwacja bez chętnych z kierunku nie blokuje miejsca (zostaje dla pozostałych grup).
+fn choose(market: &Market, c_idx: usize, mut proposals: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    proposals.sort_by_key(|&(g_idx, p_idx)| market.company_rank(p_idx, g_idx));
+
+    let mut load = vec![0; market.projects.len()];
+    let mut chosen = vec![false; proposals.len()];
+    let mut take = |i: usize| {
+        let p_idx = proposals[i].1;
+        if chosen[i] || load[p_idx] >= market.projects[p_idx].capacity {
+            return false;
+        }
+        load[p_idx] += 1;
+        chosen[i] = true;
+        true
+    };
+
+    for reservation in &market.companies[c_idx].reserved {
+        let mut taken = 0;
+        for (i, &(g_idx, _)) in proposals.iter().enumerate() {
+            if taken == reservation.seats {
+                break;
+            }
+            let on_track = market.groups[g_idx].track.as_deref() == Some(reservation.track.as_str());
+            if on_track && take(i) {
+                taken += 1;
+            }
+        }
+    }
+    for i in 0..proposals.len() {
+        take(i);
+    }
+
+    proposals.into_iter()
+        .zip(chosen)
+        .filter_map(|(proposal, chosen)| chosen.then_some(proposal))
+        .collect()
+}
+
+// Odroczona akceptacja (proponują grupy) z wyborem firm uwzględniającym rezerwacje
+fn reserve_proposing(market: &Market) -> Vec<Option<usize>> {
+    let mut free_groups: VecDeque<usize> = (0..market.groups.len()).collect();
+    let mut next_proposal = vec![0; market.groups.len()];
+    let mut held: Vec<Vec<(usize, usize)>> = vec![Vec::new(); market.companies.len()];
+
+    while let Some(g_idx) = free_groups.pop_front() {
+        let Some(&p_idx) = market.group_prefs[g_idx].get(next_proposal[g_idx]) else {
+            continue;
+        };
+        next_proposal[g_idx] += 1;
+
+        if market.company_rank(p_idx, g_idx).is_none() {
+            free_groups.push_back(g_idx);
+            continue;
+        }
+
+        let c_idx = market.project_company[p_idx];
+        let mut proposals = std::mem::take(&mut held[c_idx]);
+        proposals.push((g_idx, p_idx));
+        held[c_idx] = choose(market, c_idx, proposals.clone());
+        free_groups.extend(proposals.iter()
+            .filter(|proposal| !held[c_idx].contains(proposal))
+            .map(|&(g_idx, _)| g_idx));
+    }
+
+    let mut assignment = vec![None; market.groups.len()];
+    for &(g_idx, p_idx) in held.iter().flatten() {
+        assignment[g_idx] = Some(p_idx);
+    }
+    assignment
+}
+
+// Skojarzenie z miejscami zarezerwowanymi dla kierunków (Group::track, Company::reserved).
+// Jest stabilne względem wyboru firm z rezerwacjami; raport podaje zajęte rezerwacje
+// i liczbę par blokujących w zwykłym sensie, czyli koszt rezerwacji.
+pub fn track_reserve_matching(groups: &[Group], companies: &[Company], projects: &[Project]) -> (Vec<MatchResult>, ReservationReport) {
+    let market = Market::new(groups, companies, projects);
+    let assignment = reserve_proposing(&market);
+
+    let seats = companies.iter()
+        .enumerate()
+        .flat_map(|(c_idx, company)| {
+            let assignment = &assignment;
+            let market = &market;
+            company.reserved.iter().map(move |reservation| {
+                let on_track = assignment.iter()
+                    .enumerate()
+                    .filter(|(g_idx, p_idx)| {
+                        p_idx.is_some_and(|p_idx| market.project_company[p_idx] == c_idx)
+                            && groups[*g_idx].track.as_deref() == Some(reservation.track.as_str())
+                    })
+                    .count();
+                ReservedSeats {
+                    company: company.name.clone(),
+                    track: reservation.track.clone(),
+                    reserved: reservation.seats,
+                    filled: on_track.min(reservation.seats),
+                }
+            })
+        })
+        .collect();
+
+    let results = market.results(&assignment);
+    let report = ReservationReport {
+        seats,
+        blocking_pairs: verify_stability(groups, companies, projects, &results).blocking_pairs.len(),
+    };
+    (results, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::group_proposing;
+    use crate::matching::testing::{group, company, project, tied_instance};
+    use crate::models::TrackReservation;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    const TRACKS: [&str; 2] = ["bachelor", "master"];
+
+    #[test]
+    fn reservations_leave_no_justified_envy() {
+        for seed in 0..300 {
+            let (mut groups, mut companies, projects) = tied_instance(seed);
+            let mut rng = ChaCha8Rng::seed_from_u64(seed + 2000);
+            for g in &mut groups {
+                g.track = Some(TRACKS[rng.gen_range(0..2)].to_string());
+            }
+
+            // Bez rezerwacji to zwykły Gale-Shapley
+            let market = Market::new(&groups, &companies, &projects);
+            assert_eq!(reserve_proposing(&market), group_proposing(&market), "seed {}", seed);
+
+            for c in &mut companies {
+                c.reserved = vec![TrackReservation {
+                    track: TRACKS[rng.gen_range(0..2)].to_string(),
+                    seats: rng.gen_range(0..=2),
+                }];
+            }
+            let market = Market::new(&groups, &companies, &projects);
+            let assignment = reserve_proposing(&market);
+
+            for p_idx in 0..market.projects.len() {
+                let held = assignment.iter().filter(|&&a| a == Some(p_idx)).count();
+                assert!(held <= market.projects[p_idx].capacity, "seed {}", seed);
+            }
+
+            // Żadna grupa nie woli projektu, którego firma wybrałaby ją zamiast obecnych grup
+            for g_idx in 0..groups.len() {
+                let current = assignment[g_idx].map(|p_idx| market.group_rank(g_idx, p_idx).unwrap());
+                for (rank, &p_idx) in market.group_prefs[g_idx].iter().enumerate() {
+                    if current.is_some_and(|c| rank >= c) || market.company_rank(p_idx, g_idx).is_none() {
+                        continue;
+                    }
+                    let c_idx = market.project_company[p_idx];
+                    let mut proposals: Vec<(usize, usize)> = assignment.iter()
+                        .enumerate()
+                        .filter_map(|(h_idx, a)| a.filter(|&q| market.project_company[q] == c_idx).map(|q| (h_idx, q)))
+                        .collect();
+                    proposals.push((g_idx, p_idx));
+                    assert!(!choose(&market, c_idx, proposals).contains(&(g_idx, p_idx)), "seed {}", seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reserved_seat_goes_to_the_track() {
+        let group = |name: &str, track: &str| Group { track: Some(track.into()), ..group(name, &["acme"]) };
+        let groups = vec![group("g1", "bachelor"), group("g2", "bachelor"), group("g3", "master")];
+        let companies = vec![Company {
+            reserved: vec![TrackReservation { track: "master".into(), seats: 1 }],
+            ..company("acme", &["g1", "g2", "g3"])
+        }];
+        let projects = vec![project("p", "acme", 2)];
+
+        let (results, report) = track_reserve_matching(&groups, &companies, &projects);
+        let matched: Vec<&str> = results.iter().map(|m| m.group.as_str()).collect();
+        assert_eq!(matched, ["g1", "g3"]);
+        assert_eq!(report.seats[0].filled, 1);
+        assert_eq!(report.blocking_pairs, 1);
+    }
+}
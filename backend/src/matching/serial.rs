@@ -0,0 +1,94 @@
+use super::Market;
+use crate::models::{Group, Company, Project, MatchResult};
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+// Losowa dyktatura seryjna: grupy w losowej kolejności biorą najwyżej ocenione wolne miejsce.
+// Listy firm nie są brane pod uwagę, więc działa także przy niepełnych rankingach firm.
+pub fn random_serial_dictatorship(groups: &[Group], companies: &[Company], projects: &[Project], seed: u64) -> Vec<MatchResult> {
+    if groups.is_empty() || companies.is_empty() {
+        return vec![];
+    }
+
+    let market = Market::new(groups, companies, projects);
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+    order.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+
+    let mut held = vec![0; market.projects.len()];
+    let mut assignment = vec![None; groups.len()];
+
+    for g_idx in order {
+        let pick = market.group_prefs[g_idx].iter()
+            .copied()
+            .find(|&p_idx| held[p_idx] < market.projects[p_idx].capacity);
+        if let Some(p_idx) = pick {
+            held[p_idx] += 1;
+            assignment[g_idx] = Some(p_idx);
+        }
+    }
+    market.results(&assignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::testing::tied_instance;
+
+    fn pairs(results: &[MatchResult]) -> Vec<(String, String)> {
+        results.iter().map(|m| (m.group.clone(), m.project.clone())).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_matching_and_capacities_hold() {
+        for seed in 0..100 {
+            let (groups, companies, projects) = tied_instance(seed);
+            let first = random_serial_dictatorship(&groups, &companies, &projects, seed);
+            let second = random_serial_dictatorship(&groups, &companies, &projects, seed);
+            assert_eq!(pairs(&first), pairs(&second));
+
+            for p in &projects {
+                assert!(first.iter().filter(|m| m.project == p.id).count() <= p.capacity);
+            }
+        }
+    }
+
+    #[test]
+    fn no_group_passes_over_a_seat_free_in_its_turn() {
+        let seed = 42;
+        for instance in 0..200 {
+            let (mut groups, companies, projects) = tied_instance(instance);
+            // Ścisłe listy: "wyżej" znaczy wcześniej na liście grupy
+            groups.iter_mut().for_each(|g| g.tiers.clear());
+            let results = random_serial_dictatorship(&groups, &companies, &projects, seed);
+            assert_eq!(pairs(&results), pairs(&random_serial_dictatorship(&groups, &companies, &projects, seed)));
+
+            // Kolejność wyboru z tego samego seeda; każdy projekt wyżej niż przydział musiał być już pełny
+            let market = Market::new(&groups, &companies, &projects);
+            let mut order: Vec<usize> = (0..groups.len()).collect();
+            order.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+
+            let mut taken = vec![0; market.projects.len()];
+            for g_idx in order {
+                let assigned = results.iter()
+                    .find(|m| m.group == groups[g_idx].name)
+                    .map(|m| market.projects.iter().position(|p| p.id == m.project).unwrap());
+                let prefs = &market.group_prefs[g_idx];
+                let better = match assigned {
+                    Some(p_idx) => &prefs[..prefs.iter().position(|&q| q == p_idx).unwrap()],
+                    None => &prefs[..],
+                };
+
+                for &p_idx in better {
+                    assert!(
+                        taken[p_idx] >= market.projects[p_idx].capacity,
+                        "instance {}: '{}' passed over free '{}'", instance, groups[g_idx].name, market.projects[p_idx].id
+                    );
+                }
+                if let Some(p_idx) = assigned {
+                    taken[p_idx] += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use super::eligibility::restrict_lists;
+use crate::models::{Group, Company, Project, Application, ApplicationStatus};
+
+// Czy zgłoszenie dotyczy pary (grupa, projekt)
+fn covers(application: &Application, group: &str, project: &Project) -> bool {
+    application.group == group
+        && application.company == project.company
+        && application.project.as_ref().is_none_or(|p| *p == project.id)
+}
+
+// Listy obu stron ograniczone do par objętych zakwalifikowanym zgłoszeniem
+pub fn shortlisted_lists(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    applications: &[Application],
+) -> (Vec<Group>, Vec<Company>) {
+    restrict_lists(groups, companies, projects, |g, p| shortlisted(applications, &g.name, p))
+}
+
+// Czy para (grupa, projekt) ma zakwalifikowane zgłoszenie
+pub(super) fn shortlisted(applications: &[Application], group: &str, project: &Project) -> bool {
+    applications.iter().any(|a| a.status == ApplicationStatus::Shortlisted && covers(a, group, project))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{company_projects, stable_matching, testing::tied_instance};
+    use crate::models::Proposer;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn only_shortlisted_pairs_are_matched() {
+        for seed in 0..300 {
+            let (groups, companies, mut projects) = tied_instance(seed);
+            let mut rng = ChaCha8Rng::seed_from_u64(seed + 4000);
+            let second: Vec<Project> = projects.iter()
+                .map(|p| Project { id: format!("{}-b", p.id), ..p.clone() })
+                .collect();
+            projects.extend(second);
+
+            // Zgłoszenia do całej firmy albo do jednego projektu, w losowym stanie
+            let statuses = [ApplicationStatus::Pending, ApplicationStatus::Shortlisted, ApplicationStatus::Rejected];
+            let mut applications = Vec::new();
+            for g in &groups {
+                for p in &projects {
+                    if rng.gen_bool(0.4) {
+                        applications.push(Application {
+                            group: g.name.clone(),
+                            company: p.company.clone(),
+                            project: rng.gen_bool(0.5).then(|| p.id.clone()),
+                            motivation: String::new(),
+                            status: statuses[rng.gen_range(0..3)],
+                        });
+                    }
+                }
+            }
+
+            let all_projects = company_projects(&companies, &projects);
+            let (short_groups, short_companies) = shortlisted_lists(&groups, &companies, &projects, &applications);
+            for proposer in [Proposer::Groups, Proposer::Companies] {
+                for m in stable_matching(&short_groups, &short_companies, &projects, proposer) {
+                    let project = all_projects.iter().find(|p| p.id == m.project).unwrap();
+                    let shortlisted = applications.iter()
+                        .any(|a| a.status == ApplicationStatus::Shortlisted && covers(a, &m.group, project));
+                    assert!(shortlisted, "seed {}: {:?}", seed, m);
+                }
+            }
+
+            // Firma ocenia tylko grupy, które przeszły selekcję do któregoś jej projektu
+            for c in &short_companies {
+                for name in &c.preferences {
+                    assert!(applications.iter().any(|a| a.status == ApplicationStatus::Shortlisted
+                        && &a.group == name && a.company == c.name), "seed {}", seed);
+                }
+            }
+        }
+    }
+}
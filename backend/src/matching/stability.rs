@@ -0,0 +1,221 @@
+use super::Market;
+use crate::models::{Group, Company, Project, MatchResult, BlockingPair, Violation, StabilityReport};
+
+pub fn verify_stability(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    matching: &[MatchResult],
+) -> StabilityReport {
+    let market = Market::new(groups, companies, projects);
+    let mut violations = Vec::new();
+    let mut assignment: Vec<Option<usize>> = vec![None; groups.len()];
+    let mut holders: Vec<Vec<usize>> = vec![Vec::new(); market.projects.len()];
+
+    for m in matching {
+        let violation = |reason: String| Violation {
+            group: m.group.clone(),
+            company: m.company.clone(),
+            project: m.project.clone(),
+            reason,
+        };
+
+        let Some(g_idx) = groups.iter().position(|g| g.name == m.group) else {
+            violations.push(violation(format!("Group '{}' does not exist", m.group)));
+            continue;
+        };
+        let Some(p_idx) = market.projects.iter().position(|p| p.id == m.project) else {
+            violations.push(violation(format!("Project '{}' does not exist", m.project)));
+            continue;
+        };
+
+        let project = &market.projects[p_idx];
+        if project.company != m.company {
+            violations.push(violation(format!(
+                "Project '{}' belongs to company '{}', not '{}'",
+                project.title, project.company, m.company
+            )));
+        }
+
+        if assignment[g_idx].is_some() {
+            violations.push(violation(format!("Group '{}' is assigned more than once", m.group)));
+            continue;
+        }
+        assignment[g_idx] = Some(p_idx);
+        holders[p_idx].push(g_idx);
+
+        if market.group_rank(g_idx, p_idx).is_none() {
+            violations.push(violation(format!(
+                "Group '{}' did not rank project '{}'",
+                m.group, project.title
+            )));
+        }
+        if market.company_rank(p_idx, g_idx).is_none() {
+            violations.push(violation(format!(
+                "Company '{}' did not rank group '{}'",
+                project.company, m.group
+            )));
+        }
+    }
+
+    for (p_idx, held) in holders.iter().enumerate() {
+        let project = &market.projects[p_idx];
+        if held.len() > project.capacity {
+            violations.push(Violation {
+                group: held.iter()
+                    .map(|&g_idx| groups[g_idx].name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                company: project.company.clone(),
+                project: project.id.clone(),
+                reason: format!(
+                    "Project '{}' holds {} groups but its capacity is {}",
+                    project.title, held.len(), project.capacity
+                ),
+            });
+        }
+    }
+
+    let mut blocking_pairs = Vec::new();
+
+    // Przy remisach blokuje tylko para, w której obie strony ściśle wolą siebie (słaba stabilność)
+    for (g_idx, group) in groups.iter().enumerate() {
+        let current_tier = assignment[g_idx].and_then(|p_idx| market.group_tier(g_idx, p_idx));
+
+        for (&p_idx, &tier) in market.group_prefs[g_idx].iter().zip(&market.group_tiers[g_idx]) {
+            if current_tier.is_some_and(|current| tier >= current) {
+                break;
+            }
+            let Some(c_tier) = market.company_tier(p_idx, g_idx) else {
+                continue;
+            };
+
+            let project = &market.projects[p_idx];
+            let company_reason = if holders[p_idx].len() < project.capacity {
+                format!(
+                    "Project '{}' has a free seat ({}/{})",
+                    project.title, holders[p_idx].len(), project.capacity
+                )
+            } else {
+                // Najgorsza trzymana grupa (nierankingowana liczy się jako najgorsza)
+                let worst = holders[p_idx].iter()
+                    .map(|&h_idx| (market.company_tier(p_idx, h_idx).unwrap_or(usize::MAX), h_idx))
+                    .max()
+                    .filter(|&(h_tier, _)| h_tier > c_tier);
+
+                match worst {
+                    Some((usize::MAX, h_idx)) => format!(
+                        "Company '{}' ranks '{}' #{} but holds unranked '{}'",
+                        project.company, group.name, c_tier + 1, groups[h_idx].name
+                    ),
+                    Some((h_tier, h_idx)) => format!(
+                        "Company '{}' ranks '{}' #{} above '{}' (#{})",
+                        project.company, group.name, c_tier + 1, groups[h_idx].name, h_tier + 1
+                    ),
+                    None => continue,
+                }
+            };
+
+            let group_reason = match (assignment[g_idx], current_tier) {
+                (None, _) => format!(
+                    "Group '{}' is unmatched and ranks '{}' #{}",
+                    group.name, project.title, tier + 1
+                ),
+                (Some(cur), Some(cur_tier)) => format!(
+                    "Group '{}' ranks '{}' #{} above its assignment '{}' (#{})",
+                    group.name, project.title, tier + 1, market.projects[cur].title, cur_tier + 1
+                ),
+                (Some(cur), None) => format!(
+                    "Group '{}' ranks '{}' #{} but is assigned to unranked '{}'",
+                    group.name, project.title, tier + 1, market.projects[cur].title
+                ),
+            };
+
+            blocking_pairs.push(BlockingPair {
+                group: group.name.clone(),
+                company: project.company.clone(),
+                project: project.id.clone(),
+                group_reason,
+                company_reason,
+            });
+        }
+    }
+
+    StabilityReport {
+        stable: blocking_pairs.is_empty() && violations.is_empty(),
+        blocking_pairs,
+        violations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::stable_matching;
+    use crate::matching::testing::{group, company, project, assign, random_instance};
+    use crate::models::Proposer;
+
+    const PROPOSERS: [Proposer; 2] = [Proposer::Groups, Proposer::Companies];
+
+    #[test]
+    fn gale_shapley_is_stable_on_complete_lists() {
+        let groups = vec![
+            group("g1", &["c1", "c2", "c3"]),
+            group("g2", &["c1", "c3", "c2"]),
+            group("g3", &["c2", "c1", "c3"]),
+            group("g4", &["c1", "c2", "c3"]),
+        ];
+        let companies = vec![
+            company("c1", &["g3", "g2", "g1", "g4"]),
+            company("c2", &["g1", "g4", "g2", "g3"]),
+            company("c3", &["g4", "g1", "g3", "g2"]),
+        ];
+        let projects = vec![project("c1", "c1", 2)];
+
+        for proposer in PROPOSERS {
+            let matching = stable_matching(&groups, &companies, &projects, proposer);
+            let report = verify_stability(&groups, &companies, &projects, &matching);
+            assert!(report.stable, "{:?}: {:?}", proposer, report);
+            assert_eq!(matching.len(), groups.len());
+        }
+    }
+
+    #[test]
+    fn gale_shapley_has_no_blocking_pairs_on_random_instances() {
+        for seed in 1..200 {
+            let (groups, companies, projects) = random_instance(seed);
+
+            for proposer in PROPOSERS {
+                let matching = stable_matching(&groups, &companies, &projects, proposer);
+                let report = verify_stability(&groups, &companies, &projects, &matching);
+                assert!(report.blocking_pairs.is_empty(), "seed {} {:?}: {:?}", seed, proposer, report);
+            }
+        }
+    }
+
+    #[test]
+    fn detects_blocking_pair() {
+        let groups = vec![group("g1", &["c1", "c2"]), group("g2", &["c1", "c2"])];
+        let companies = vec![company("c1", &["g1", "g2"]), company("c2", &["g1", "g2"])];
+        let matching = vec![assign("g1", "c2", "c2"), assign("g2", "c1", "c1")];
+
+        let report = verify_stability(&groups, &companies, &[], &matching);
+        assert!(!report.stable);
+        assert_eq!(report.blocking_pairs.len(), 1);
+        assert_eq!(report.blocking_pairs[0].group, "g1");
+        assert_eq!(report.blocking_pairs[0].company, "c1");
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn detects_unacceptable_and_over_capacity_assignments() {
+        let groups = vec![group("g1", &["c1"]), group("g2", &["c1"])];
+        let companies = vec![company("c1", &["g1"])];
+        let matching = vec![assign("g1", "c1", "c1"), assign("g2", "c1", "c1")];
+
+        let report = verify_stability(&groups, &companies, &[], &matching);
+        assert!(!report.stable);
+        assert!(report.violations.iter().any(|v| v.reason.contains("did not rank group 'g2'")));
+        assert!(report.violations.iter().any(|v| v.reason.contains("capacity is 1")));
+    }
+}
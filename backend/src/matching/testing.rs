@@ -0,0 +1,162 @@
+// Wspólne narzędzia testów: małe losowe instancje z remisami i przegląd wszystkich skojarzeń
+use super::Market;
+use crate::models::{Group, Company, Project, MatchResult};
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+pub(crate) fn group(name: &str, prefs: &[&str]) -> Group {
+    Group {
+        name: name.into(),
+        email: name.into(),
+        preferences: prefs.iter().map(|p| p.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn company(name: &str, prefs: &[&str]) -> Company {
+    Company {
+        name: name.into(),
+        email: name.into(),
+        preferences: prefs.iter().map(|p| p.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn project(id: &str, company: &str, capacity: usize) -> Project {
+    Project {
+        id: id.into(),
+        company: company.into(),
+        title: id.into(),
+        description: String::new(),
+        capacity,
+        min_capacity: 0,
+        required_skills: vec![],
+    }
+}
+
+pub(crate) fn assign(group: &str, company: &str, project: &str) -> MatchResult {
+    MatchResult {
+        group: group.into(),
+        company: company.into(),
+        project: project.into(),
+        project_title: project.into(),
+        ..Default::default()
+    }
+}
+
+// Ścisłe listy: 8 grup, 4 firmy po dwa projekty, listy obcięte losowo
+pub(crate) fn random_instance(seed: u64) -> (Vec<Group>, Vec<Company>, Vec<Project>) {
+    strict_instance(seed, 8, 4, false)
+}
+
+// Pełne listy dają zwykle wiele stabilnych skojarzeń
+pub(crate) fn dense_instance(seed: u64) -> (Vec<Group>, Vec<Company>, Vec<Project>) {
+    strict_instance(seed, 9, 3, true)
+}
+
+fn strict_instance(
+    seed: u64,
+    group_count: usize,
+    company_count: usize,
+    complete: bool,
+) -> (Vec<Group>, Vec<Company>, Vec<Project>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let group_names: Vec<String> = (0..group_count).map(|i| format!("g{}", i)).collect();
+    let company_names: Vec<String> = (0..company_count).map(|i| format!("c{}", i)).collect();
+
+    let projects: Vec<Project> = company_names.iter()
+        .flat_map(|c| [project(&format!("{}-a", c), c, 2), project(&format!("{}-b", c), c, 1)])
+        .collect();
+    let project_ids: Vec<String> = projects.iter().map(|p| p.id.clone()).collect();
+
+    let mut shuffled = |names: &[String]| {
+        let mut prefs = names.to_vec();
+        prefs.shuffle(&mut rng);
+        if !complete {
+            prefs.truncate(rng.gen_range(1..=names.len()));
+        }
+        prefs
+    };
+
+    let groups = group_names.iter()
+        .map(|g| Group { preferences: shuffled(&project_ids), ..group(g, &[]) })
+        .collect();
+    let companies = company_names.iter()
+        .map(|c| Company { preferences: shuffled(&group_names), ..company(c, &[]) })
+        .collect();
+
+    (groups, companies, projects)
+}
+
+pub(crate) fn tied_instance(seed: u64) -> (Vec<Group>, Vec<Company>, Vec<Project>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let group_names: Vec<String> = (0..rng.gen_range(2..=5)).map(|i| format!("g{}", i)).collect();
+    let company_names: Vec<String> = (0..rng.gen_range(1..=3)).map(|i| format!("c{}", i)).collect();
+
+    let projects: Vec<Project> = company_names.iter()
+        .map(|c| Project {
+            id: c.clone(),
+            company: c.clone(),
+            title: c.clone(),
+            description: String::new(),
+            capacity: rng.gen_range(1..=2),
+            min_capacity: 0,
+            required_skills: vec![],
+        })
+        .collect();
+
+    // Losowy podzbiór w losowej kolejności, kolejne pozycje często remisują z poprzednią
+    let mut tiered = |names: &[String]| {
+        let mut prefs = names.to_vec();
+        prefs.shuffle(&mut rng);
+        prefs.truncate(rng.gen_range(1..=names.len()));
+        let mut tiers = Vec::new();
+        for i in 0..prefs.len() {
+            let tie = i > 0 && rng.gen_bool(0.5);
+            tiers.push(if tie { tiers[i - 1] } else { i });
+        }
+        (prefs, tiers)
+    };
+
+    let groups = group_names.iter()
+        .map(|name| {
+            let (preferences, tiers) = tiered(&company_names);
+            Group { name: name.clone(), email: name.clone(), preferences, tiers, ..Default::default() }
+        })
+        .collect();
+    let companies = company_names.iter()
+        .map(|name| {
+            let (preferences, tiers) = tiered(&group_names);
+            Company { name: name.clone(), email: name.clone(), preferences, tiers, ..Default::default() }
+        })
+        .collect();
+
+    (groups, companies, projects)
+}
+
+// Wszystkie skojarzenia (bez przepełnień) złożone z par akceptowalnych dla obu stron
+pub(super) fn all_matchings(market: &Market) -> Vec<Vec<Option<usize>>> {
+    fn extend(market: &Market, g_idx: usize, current: &mut Vec<Option<usize>>, out: &mut Vec<Vec<Option<usize>>>) {
+        if g_idx == market.groups.len() {
+            out.push(current.clone());
+            return;
+        }
+        current.push(None);
+        extend(market, g_idx + 1, current, out);
+        current.pop();
+
+        for &p_idx in &market.group_prefs[g_idx] {
+            let held = current.iter().filter(|&&p| p == Some(p_idx)).count();
+            if market.company_rank(p_idx, g_idx).is_some() && held < market.projects[p_idx].capacity {
+                current.push(Some(p_idx));
+                extend(market, g_idx + 1, current, out);
+                current.pop();
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    extend(market, 0, &mut Vec::new(), &mut out);
+    out
+}
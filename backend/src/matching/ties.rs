@@ -0,0 +1,593 @@
+use super::{Market, group_proposing, preference_tiers};
+use crate::models::{Group, Company, Project, MatchResult, BlockingPair, TieReport};
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Criterion {
+    // Para blokuje, gdy obie strony wolą siebie co najmniej tak samo
+    Super,
+    // Para blokuje, gdy jedna strona woli ściśle, a druga co najmniej tak samo
+    Strong,
+}
+
+fn shuffle_tiers(preferences: &[String], tiers: &[usize], rng: &mut ChaCha8Rng) -> Vec<String> {
+    let tiers = preference_tiers(preferences, tiers);
+    let entries: Vec<(usize, &String)> = tiers.into_iter().zip(preferences).collect();
+
+    entries.chunk_by(|a, b| a.0 == b.0)
+        .flat_map(|tier| {
+            let mut names: Vec<String> = tier.iter().map(|(_, name)| (*name).clone()).collect();
+            names.shuffle(rng);
+            names
+        })
+        .collect()
+}
+
+// Losowa kolejność w obrębie każdego poziomu; ten sam seed daje te same listy
+pub fn break_ties(groups: &[Group], companies: &[Company], seed: u64) -> (Vec<Group>, Vec<Company>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    let groups = groups.iter()
+        .map(|g| Group {
+            preferences: shuffle_tiers(&g.preferences, &g.tiers, &mut rng),
+            tiers: vec![],
+            ..g.clone()
+        })
+        .collect();
+    let companies = companies.iter()
+        .map(|c| Company {
+            preferences: shuffle_tiers(&c.preferences, &c.tiers, &mut rng),
+            tiers: vec![],
+            ..c.clone()
+        })
+        .collect();
+
+    (groups, companies)
+}
+
+// Jedna loteria dla wszystkich firm: remis na liście firmy wygrywa grupa z niższym numerem losu
+pub fn lottery_ties(groups: &[Group], companies: &[Company], seed: u64) -> Vec<Company> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut draw: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+    draw.shuffle(&mut rng);
+
+    let ticket = |name: &str| draw.iter().position(|&g| g == name).unwrap_or(draw.len());
+
+    companies.iter()
+        .map(|c| {
+            let mut entries: Vec<(usize, usize, &String)> = preference_tiers(&c.preferences, &c.tiers).into_iter()
+                .zip(&c.preferences)
+                .map(|(tier, name)| (tier, ticket(name), name))
+                .collect();
+            entries.sort();
+            Company {
+                preferences: entries.into_iter().map(|(_, _, name)| name.clone()).collect(),
+                tiers: vec![],
+                ..c.clone()
+            }
+        })
+        .collect()
+}
+
+// Tymczasowe przydziały algorytmów dla list z remisami (Irving, Manlove, Scott).
+// Grupa może trzymać kilka projektów naraz, projekt ponad limit; pary są usuwane na stałe.
+struct Provisional {
+    // Pary akceptowalne dla obu stron: (projekt, poziom) oraz (grupa, poziom)
+    group_lists: Vec<Vec<(usize, usize)>>,
+    project_lists: Vec<Vec<(usize, usize)>>,
+    capacity: Vec<usize>,
+    deleted: HashSet<(usize, usize)>,
+    assigned: Vec<Vec<usize>>,
+    holders: Vec<Vec<usize>>,
+}
+
+impl Provisional {
+    fn new(market: &Market) -> Self {
+        let capacity: Vec<usize> = market.projects.iter().map(|p| p.capacity).collect();
+
+        let group_lists = (0..market.groups.len())
+            .map(|g_idx| market.group_prefs[g_idx].iter()
+                .zip(&market.group_tiers[g_idx])
+                .filter(|&(&p_idx, _)| capacity[p_idx] > 0 && market.company_rank(p_idx, g_idx).is_some())
+                .map(|(&p_idx, &tier)| (p_idx, tier))
+                .collect())
+            .collect();
+
+        let project_lists = (0..market.projects.len())
+            .map(|p_idx| {
+                let mut seen = HashSet::new();
+                let c_idx = market.project_company[p_idx];
+                market.company_prefs[c_idx].iter()
+                    .zip(&market.company_tiers[c_idx])
+                    .filter(|&(&g_idx, _)| {
+                        capacity[p_idx] > 0 && market.group_rank(g_idx, p_idx).is_some() && seen.insert(g_idx)
+                    })
+                    .map(|(&g_idx, &tier)| (g_idx, tier))
+                    .collect()
+            })
+            .collect();
+
+        Provisional {
+            group_lists,
+            project_lists,
+            deleted: HashSet::new(),
+            assigned: vec![Vec::new(); market.groups.len()],
+            holders: vec![Vec::new(); capacity.len()],
+            capacity,
+        }
+    }
+
+    fn assign(&mut self, g_idx: usize, p_idx: usize) {
+        self.assigned[g_idx].push(p_idx);
+        self.holders[p_idx].push(g_idx);
+    }
+
+    fn delete(&mut self, g_idx: usize, p_idx: usize) {
+        self.deleted.insert((g_idx, p_idx));
+        self.assigned[g_idx].retain(|&p| p != p_idx);
+        self.holders[p_idx].retain(|&g| g != g_idx);
+    }
+
+    fn tier_at(&self, p_idx: usize, g_idx: usize) -> usize {
+        self.project_lists[p_idx].iter()
+            .find(|&&(g, _)| g == g_idx)
+            .map(|&(_, tier)| tier)
+            .unwrap()
+    }
+
+    fn remaining(&self, p_idx: usize) -> Vec<(usize, usize)> {
+        self.project_lists[p_idx].iter()
+            .copied()
+            .filter(|&(g_idx, _)| !self.deleted.contains(&(g_idx, p_idx)))
+            .collect()
+    }
+
+    // Projekty z najlepszego poziomu, który grupie jeszcze został
+    fn head(&self, g_idx: usize) -> Vec<usize> {
+        let remaining: Vec<(usize, usize)> = self.group_lists[g_idx].iter()
+            .copied()
+            .filter(|&(p_idx, _)| !self.deleted.contains(&(g_idx, p_idx)))
+            .collect();
+        let Some(best) = remaining.iter().map(|&(_, tier)| tier).min() else {
+            return vec![];
+        };
+        remaining.into_iter()
+            .filter(|&(_, tier)| tier == best)
+            .map(|(p_idx, _)| p_idx)
+            .collect()
+    }
+
+    fn next_free_group(&self) -> Option<usize> {
+        (0..self.assigned.len()).find(|&g_idx| self.assigned[g_idx].is_empty() && !self.head(g_idx).is_empty())
+    }
+
+    fn tail_tier(&self, p_idx: usize) -> Option<usize> {
+        self.remaining(p_idx).iter().map(|&(_, tier)| tier).max()
+    }
+
+    fn delete_worse_than(&mut self, p_idx: usize, tier: usize) {
+        for (g_idx, t) in self.remaining(p_idx) {
+            if t > tier {
+                self.delete(g_idx, p_idx);
+            }
+        }
+    }
+
+    fn delete_tail(&mut self, p_idx: usize) {
+        if let Some(tail) = self.tail_tier(p_idx) {
+            for (g_idx, t) in self.remaining(p_idx) {
+                if t == tail {
+                    self.delete(g_idx, p_idx);
+                }
+            }
+        }
+    }
+
+    // Grupa zdominowana: projekt ma co najmniej `capacity` przydziałów, które woli ściśle
+    fn delete_dominated(&mut self, p_idx: usize) {
+        let mut tiers: Vec<usize> = self.holders[p_idx].iter().map(|&g_idx| self.tier_at(p_idx, g_idx)).collect();
+        tiers.sort();
+        if let Some(&tier) = tiers.get(self.capacity[p_idx] - 1) {
+            self.delete_worse_than(p_idx, tier);
+        }
+    }
+
+    // Grupa jest związana z projektem, jeśli projekt nie jest przepełniony albo grupa nie leży w ogonie jego listy
+    fn bound(&self, g_idx: usize) -> Vec<usize> {
+        self.assigned[g_idx].iter()
+            .copied()
+            .filter(|&p_idx| {
+                self.holders[p_idx].len() <= self.capacity[p_idx]
+                    || Some(self.tier_at(p_idx, g_idx)) < self.tail_tier(p_idx)
+            })
+            .collect()
+    }
+
+    // Projekty sąsiadujące ze zbiorem krytycznym grafu zredukowanego (zbiorem grup o największym deficycie)
+    fn critical_projects(&self) -> Vec<usize> {
+        let bound: Vec<Vec<usize>> = (0..self.assigned.len()).map(|g_idx| self.bound(g_idx)).collect();
+
+        let mut quota = self.capacity.clone();
+        for p_idx in bound.iter().flatten() {
+            quota[*p_idx] = quota[*p_idx].saturating_sub(1);
+        }
+        let reduced: Vec<Vec<usize>> = (0..self.assigned.len())
+            .map(|g_idx| if bound[g_idx].is_empty() { self.assigned[g_idx].clone() } else { vec![] })
+            .collect();
+
+        let order: Vec<usize> = (0..reduced.len()).collect();
+        let matching = max_b_matching(&reduced, &quota, &order);
+
+        // Grupy osiągalne ścieżkami naprzemiennymi z nieskojarzonych grup
+        let mut visited_groups: Vec<bool> = (0..reduced.len())
+            .map(|g_idx| matching[g_idx].is_none() && !reduced[g_idx].is_empty())
+            .collect();
+        let mut visited_projects = vec![false; quota.len()];
+        let mut stack: Vec<usize> = (0..reduced.len()).filter(|&g_idx| visited_groups[g_idx]).collect();
+
+        while let Some(g_idx) = stack.pop() {
+            for &p_idx in &reduced[g_idx] {
+                if visited_projects[p_idx] {
+                    continue;
+                }
+                visited_projects[p_idx] = true;
+                for h_idx in 0..reduced.len() {
+                    if matching[h_idx] == Some(p_idx) && !visited_groups[h_idx] {
+                        visited_groups[h_idx] = true;
+                        stack.push(h_idx);
+                    }
+                }
+            }
+        }
+
+        (0..quota.len()).filter(|&p_idx| visited_projects[p_idx]).collect()
+    }
+
+    // Największe skojarzenie w grafie przydziałów; grupy związane dostają jeden ze swoich projektów
+    fn feasible_matching(&self) -> Vec<Option<usize>> {
+        let bound: Vec<Vec<usize>> = (0..self.assigned.len()).map(|g_idx| self.bound(g_idx)).collect();
+        let adjacency: Vec<Vec<usize>> = (0..self.assigned.len())
+            .map(|g_idx| if bound[g_idx].is_empty() { self.assigned[g_idx].clone() } else { bound[g_idx].clone() })
+            .collect();
+
+        let mut order: Vec<usize> = (0..adjacency.len()).collect();
+        order.sort_by_key(|&g_idx| bound[g_idx].is_empty());
+        max_b_matching(&adjacency, &self.capacity, &order)
+    }
+}
+
+// Ścieżki powiększające (Kuhn) dla skojarzenia z pojemnościami po stronie projektów
+fn max_b_matching(adjacency: &[Vec<usize>], capacity: &[usize], order: &[usize]) -> Vec<Option<usize>> {
+    let mut matching = vec![None; adjacency.len()];
+    let mut holders = vec![Vec::new(); capacity.len()];
+
+    for &g_idx in order {
+        let mut visited = vec![false; capacity.len()];
+        augment(g_idx, adjacency, capacity, &mut matching, &mut holders, &mut visited);
+    }
+    matching
+}
+
+fn augment(
+    g_idx: usize,
+    adjacency: &[Vec<usize>],
+    capacity: &[usize],
+    matching: &mut [Option<usize>],
+    holders: &mut [Vec<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &p_idx in &adjacency[g_idx] {
+        if visited[p_idx] {
+            continue;
+        }
+        visited[p_idx] = true;
+
+        if holders[p_idx].len() < capacity[p_idx] {
+            holders[p_idx].push(g_idx);
+            matching[g_idx] = Some(p_idx);
+            return true;
+        }
+        for i in 0..holders[p_idx].len() {
+            let h_idx = holders[p_idx][i];
+            if augment(h_idx, adjacency, capacity, matching, holders, visited) {
+                holders[p_idx][i] = g_idx;
+                matching[g_idx] = Some(p_idx);
+                return true;
+            }
+        }
+    }
+    false
+}
+
+// Algorytm HRT-super-res: None, jeśli skojarzenie superstabilne nie istnieje
+fn super_stable(market: &Market) -> Option<Vec<Option<usize>>> {
+    let mut state = Provisional::new(market);
+    let mut was_full = vec![false; state.capacity.len()];
+
+    while let Some(g_idx) = state.next_free_group() {
+        for p_idx in state.head(g_idx) {
+            state.assign(g_idx, p_idx);
+
+            if state.holders[p_idx].len() > state.capacity[p_idx] {
+                state.delete_tail(p_idx);
+            }
+            if state.holders[p_idx].len() == state.capacity[p_idx] {
+                was_full[p_idx] = true;
+                let worst = state.holders[p_idx].iter().map(|&h_idx| state.tier_at(p_idx, h_idx)).max();
+                if let Some(worst) = worst {
+                    state.delete_worse_than(p_idx, worst);
+                }
+            }
+        }
+    }
+
+    let multiply_assigned = state.assigned.iter().any(|a| a.len() > 1);
+    let emptied = (0..was_full.len()).any(|p_idx| was_full[p_idx] && state.holders[p_idx].len() < state.capacity[p_idx]);
+    if multiply_assigned || emptied {
+        return None;
+    }
+    Some(state.assigned.iter().map(|a| a.first().copied()).collect())
+}
+
+// Algorytm HRT-strong-res: None, jeśli skojarzenie silnie stabilne nie istnieje
+fn strongly_stable(market: &Market) -> Option<Vec<Option<usize>>> {
+    let mut state = Provisional::new(market);
+
+    loop {
+        while let Some(g_idx) = state.next_free_group() {
+            for p_idx in state.head(g_idx) {
+                state.assign(g_idx, p_idx);
+                if state.holders[p_idx].len() >= state.capacity[p_idx] {
+                    state.delete_dominated(p_idx);
+                }
+            }
+        }
+
+        let critical = state.critical_projects();
+        if critical.is_empty() {
+            break;
+        }
+        for p_idx in critical {
+            state.delete_tail(p_idx);
+        }
+    }
+
+    let assignment = state.feasible_matching();
+    blocking_pairs(market, &assignment, Criterion::Strong).is_empty().then_some(assignment)
+}
+
+fn blocking_pairs(market: &Market, assignment: &[Option<usize>], criterion: Criterion) -> Vec<BlockingPair> {
+    let mut holders: Vec<Vec<usize>> = vec![Vec::new(); market.projects.len()];
+    for (g_idx, p_idx) in assignment.iter().enumerate() {
+        if let Some(p_idx) = p_idx {
+            holders[*p_idx].push(g_idx);
+        }
+    }
+
+    let mut result = Vec::new();
+
+    for (g_idx, group) in market.groups.iter().enumerate() {
+        // Brak przydziału albo przydział spoza listy jest gorszy od wszystkiego na liście
+        let current_tier = assignment[g_idx].and_then(|p_idx| market.group_tier(g_idx, p_idx));
+
+        for (&p_idx, &tier) in market.group_prefs[g_idx].iter().zip(&market.group_tiers[g_idx]) {
+            if assignment[g_idx] == Some(p_idx) {
+                continue;
+            }
+            let Some(c_tier) = market.company_tier(p_idx, g_idx) else {
+                continue;
+            };
+            let project = &market.projects[p_idx];
+
+            let group_strict = current_tier.is_none_or(|current| tier < current);
+            let group_weak = current_tier.is_none_or(|current| tier <= current);
+
+            let worst = holders[p_idx].iter()
+                .map(|&h_idx| (market.company_tier(p_idx, h_idx).unwrap_or(usize::MAX), h_idx))
+                .max();
+            let free_seat = holders[p_idx].len() < project.capacity;
+            let company_strict = free_seat || worst.is_some_and(|(h_tier, _)| c_tier < h_tier);
+            let company_weak = free_seat || worst.is_some_and(|(h_tier, _)| c_tier <= h_tier);
+
+            let blocks = match criterion {
+                Criterion::Super => group_weak && company_weak,
+                Criterion::Strong => (group_strict && company_weak) || (group_weak && company_strict),
+            };
+            if !blocks {
+                continue;
+            }
+
+            let group_reason = match (assignment[g_idx], current_tier) {
+                (None, _) => format!(
+                    "Group '{}' is unmatched and ranks '{}' #{}",
+                    group.name, project.title, tier + 1
+                ),
+                (Some(cur), Some(cur_tier)) if cur_tier == tier => format!(
+                    "Group '{}' ranks '{}' #{}, tied with its assignment '{}'",
+                    group.name, project.title, tier + 1, market.projects[cur].title
+                ),
+                (Some(cur), Some(cur_tier)) => format!(
+                    "Group '{}' ranks '{}' #{} above its assignment '{}' (#{})",
+                    group.name, project.title, tier + 1, market.projects[cur].title, cur_tier + 1
+                ),
+                (Some(cur), None) => format!(
+                    "Group '{}' ranks '{}' #{} but is assigned to unranked '{}'",
+                    group.name, project.title, tier + 1, market.projects[cur].title
+                ),
+            };
+
+            let company_reason = match worst {
+                _ if free_seat => format!(
+                    "Project '{}' has a free seat ({}/{})",
+                    project.title, holders[p_idx].len(), project.capacity
+                ),
+                Some((usize::MAX, h_idx)) => format!(
+                    "Company '{}' ranks '{}' #{} but holds unranked '{}'",
+                    project.company, group.name, c_tier + 1, market.groups[h_idx].name
+                ),
+                Some((h_tier, h_idx)) if h_tier == c_tier => format!(
+                    "Company '{}' ranks '{}' #{}, tied with '{}'",
+                    project.company, group.name, c_tier + 1, market.groups[h_idx].name
+                ),
+                Some((h_tier, h_idx)) => format!(
+                    "Company '{}' ranks '{}' #{} above '{}' (#{})",
+                    project.company, group.name, c_tier + 1, market.groups[h_idx].name, h_tier + 1
+                ),
+                None => unreachable!(),
+            };
+
+            result.push(BlockingPair {
+                group: group.name.clone(),
+                company: project.company.clone(),
+                project: project.id.clone(),
+                group_reason,
+                company_reason,
+            });
+        }
+    }
+    result
+}
+
+fn tie_stable_matching(
+    groups: &[Group],
+    companies: &[Company],
+    projects: &[Project],
+    criterion: Criterion,
+) -> (Vec<MatchResult>, TieReport) {
+    if groups.is_empty() || companies.is_empty() {
+        return (vec![], TieReport {
+            exists: true,
+            message: "No groups or companies to match".into(),
+            blocking_pairs: vec![],
+        });
+    }
+
+    let market = Market::new(groups, companies, projects);
+    let found = match criterion {
+        Criterion::Super => super_stable(&market),
+        Criterion::Strong => strongly_stable(&market),
+    };
+    let exists = found.is_some();
+    let name = match criterion {
+        Criterion::Super => "super-stable",
+        Criterion::Strong => "strongly stable",
+    };
+
+    // Gdy takie skojarzenie nie istnieje, zwracamy słabo stabilne (remisy według kolejności na liście)
+    let assignment = found.unwrap_or_else(|| group_proposing(&market));
+    let blocking_pairs = blocking_pairs(&market, &assignment, criterion);
+
+    let message = if exists {
+        format!("Found a {} matching", name)
+    } else {
+        format!(
+            "No {} matching exists; returning a weakly stable matching with ties broken by list order",
+            name
+        )
+    };
+
+    (market.results(&assignment), TieReport { exists, message, blocking_pairs })
+}
+
+pub fn super_stable_matching(groups: &[Group], companies: &[Company], projects: &[Project]) -> (Vec<MatchResult>, TieReport) {
+    tie_stable_matching(groups, companies, projects, Criterion::Super)
+}
+
+pub fn strongly_stable_matching(groups: &[Group], companies: &[Company], projects: &[Project]) -> (Vec<MatchResult>, TieReport) {
+    tie_stable_matching(groups, companies, projects, Criterion::Strong)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::testing::{group, company, tied_instance, all_matchings};
+
+    #[test]
+    fn algorithms_agree_with_brute_force_on_existence() {
+        for seed in 0..400 {
+            let (groups, companies, projects) = tied_instance(seed);
+            let market = Market::new(&groups, &companies, &projects);
+            let matchings = all_matchings(&market);
+
+            for criterion in [Criterion::Super, Criterion::Strong] {
+                let exists = matchings.iter().any(|m| blocking_pairs(&market, m, criterion).is_empty());
+                let found = match criterion {
+                    Criterion::Super => super_stable(&market),
+                    Criterion::Strong => strongly_stable(&market),
+                };
+
+                assert_eq!(found.is_some(), exists, "seed {} {:?}", seed, criterion);
+                if let Some(found) = found {
+                    assert!(blocking_pairs(&market, &found, criterion).is_empty(), "seed {} {:?}", seed, criterion);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_when_no_matching_exists() {
+        let groups = vec![group("g1", &["c1"]), group("g2", &["c1"])];
+        // Firma jest obojętna między g1 i g2, a ma tylko jedno miejsce
+        let companies = vec![Company { tiers: vec![0, 0], ..company("c1", &["g1", "g2"]) }];
+
+        for (results, report) in [
+            super_stable_matching(&groups, &companies, &[]),
+            strongly_stable_matching(&groups, &companies, &[]),
+        ] {
+            assert!(!report.exists);
+            assert_eq!(results.len(), 1);
+            assert_eq!(report.blocking_pairs.len(), 1);
+            assert_eq!(report.blocking_pairs[0].group, "g2");
+        }
+    }
+
+    #[test]
+    fn random_tie_breaking_is_reproducible_and_keeps_tiers() {
+        let (groups, companies, _) = tied_instance(7);
+        let (first, _) = break_ties(&groups, &companies, 42);
+        let (second, _) = break_ties(&groups, &companies, 42);
+
+        for ((g, a), b) in groups.iter().zip(&first).zip(&second) {
+            assert_eq!(a.preferences, b.preferences);
+            assert!(a.tiers.is_empty());
+
+            let tiers = preference_tiers(&g.preferences, &g.tiers);
+            for (name, tier) in g.preferences.iter().zip(&tiers) {
+                let position = a.preferences.iter().position(|p| p == name).unwrap();
+                let before = tiers.iter().filter(|&t| t < tier).count();
+                let within = tiers.iter().filter(|&t| t == tier).count();
+                assert!(position >= before && position < before + within);
+            }
+        }
+    }
+
+    #[test]
+    fn lottery_orders_tied_groups_the_same_way_everywhere() {
+        for seed in 0..50 {
+            let (groups, companies, _) = tied_instance(seed);
+            let lottery = lottery_ties(&groups, &companies, seed);
+            let again = lottery_ties(&groups, &companies, seed);
+            assert!(lottery.iter().zip(&again).all(|(a, b)| a.preferences == b.preferences));
+
+            let mut before: Vec<(String, String)> = Vec::new();
+            for (c, broken) in companies.iter().zip(&lottery) {
+                assert!(broken.tiers.is_empty());
+                let tiers = preference_tiers(&c.preferences, &c.tiers);
+                let tier = |name: &String| tiers[c.preferences.iter().position(|p| p == name).unwrap()];
+
+                for (i, a) in broken.preferences.iter().enumerate() {
+                    for b in &broken.preferences[i + 1..] {
+                        assert!(tier(a) <= tier(b));
+                        if tier(a) == tier(b) {
+                            before.push((a.clone(), b.clone()));
+                        }
+                    }
+                }
+            }
+            assert!(before.iter().all(|(a, b)| !before.contains(&(b.clone(), a.clone()))));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use super::Market;
+use crate::models::{Group, Company, Project, MatchResult, Priority};
+use std::cmp::Ordering;
+
+// Pełna kolejność pierwszeństwa grup dla każdej firmy
+fn priority_order(market: &Market, rule: Priority) -> Vec<Vec<usize>> {
+    let score = |g_idx: usize| market.groups[g_idx].priority.unwrap_or(f64::NEG_INFINITY);
+
+    (0..market.companies.len())
+        .map(|c_idx| {
+            let tier = |g_idx: usize| match rule {
+                Priority::Company => market.company_prefs[c_idx].iter()
+                    .position(|&g| g == g_idx)
+                    .map_or(usize::MAX, |rank| market.company_tiers[c_idx][rank]),
+                Priority::Score => 0,
+            };
+            let mut order: Vec<usize> = (0..market.groups.len()).collect();
+            order.sort_by(|&a, &b| tier(a).cmp(&tier(b))
+                .then_with(|| score(b).partial_cmp(&score(a)).unwrap_or(Ordering::Equal))
+                .then_with(|| market.groups[a].name.cmp(&market.groups[b].name)));
+            order
+        })
+        .collect()
+}
+
+// Top Trading Cycles: grupa wskazuje najlepszy projekt z wolnym miejscem, projekt wskazuje grupę
+// o najwyższym pierwszeństwie; grupy z każdego cyklu dostają wskazany projekt
+pub fn top_trading_cycles(groups: &[Group], companies: &[Company], projects: &[Project], rule: Priority) -> Vec<MatchResult> {
+    if groups.is_empty() || companies.is_empty() {
+        return vec![];
+    }
+
+    let market = Market::new(groups, companies, projects);
+    let order = priority_order(&market, rule);
+    let group_count = groups.len();
+    let mut capacity: Vec<usize> = market.projects.iter().map(|p| p.capacity).collect();
+    let mut active = vec![true; group_count];
+    let mut assignment = vec![None; group_count];
+
+    loop {
+        // Wierzchołki 0..G to grupy, G.. to projekty
+        let mut points = vec![usize::MAX; group_count + market.projects.len()];
+        for g_idx in 0..group_count {
+            if !active[g_idx] {
+                continue;
+            }
+            match market.group_prefs[g_idx].iter().find(|&&p_idx| capacity[p_idx] > 0) {
+                Some(&p_idx) => points[g_idx] = group_count + p_idx,
+                None => active[g_idx] = false,
+            }
+        }
+        if !active.contains(&true) {
+            break;
+        }
+        for (p_idx, &cap) in capacity.iter().enumerate() {
+            if cap > 0 {
+                let c_idx = market.project_company[p_idx];
+                points[group_count + p_idx] = *order[c_idx].iter().find(|&&g| active[g]).unwrap();
+            }
+        }
+
+        let mut visited = vec![false; points.len()];
+        let mut cycles = Vec::new();
+        for start in (0..group_count).filter(|&g| active[g]) {
+            let mut path = Vec::new();
+            let mut node = start;
+            while !visited[node] {
+                visited[node] = true;
+                path.push(node);
+                node = points[node];
+            }
+            if let Some(from) = path.iter().position(|&n| n == node) {
+                cycles.push(path.split_off(from));
+            }
+        }
+
+        for cycle in cycles {
+            for g_idx in cycle.into_iter().filter(|&n| n < group_count) {
+                let p_idx = points[g_idx] - group_count;
+                assignment[g_idx] = Some(p_idx);
+                capacity[p_idx] -= 1;
+                active[g_idx] = false;
+            }
+        }
+    }
+    market.results(&assignment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::testing::tied_instance;
+
+    // Losowe małe instancje ze ścisłymi listami grup i losowymi wynikami priorytetowymi
+    fn instance(seed: u64) -> (Vec<Group>, Vec<Company>, Vec<Project>) {
+        let (groups, companies, projects) = tied_instance(seed);
+        let groups = groups.into_iter()
+            .enumerate()
+            .map(|(i, g)| Group {
+                tiers: vec![],
+                priority: Some(((seed as usize * 7 + i * 13) % 10) as f64),
+                ..g
+            })
+            .collect();
+        (groups, companies, projects)
+    }
+
+    // Pozycja przydziału na prawdziwej liście grupy; brak przydziału jest najgorszy
+    fn rank(group: &Group, results: &[MatchResult]) -> usize {
+        results.iter()
+            .find(|m| m.group == group.name)
+            .and_then(|m| group.preferences.iter().position(|p| *p == m.project))
+            .unwrap_or(group.preferences.len())
+    }
+
+    // Wszystkie przydziały zgodne z listami grup i pojemnościami (listy firm nie ograniczają TTC)
+    fn assignments(groups: &[Group], projects: &[Project]) -> Vec<Vec<usize>> {
+        fn extend(groups: &[Group], projects: &[Project], current: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+            let g_idx = current.len();
+            if g_idx == groups.len() {
+                out.push(current.clone());
+                return;
+            }
+            for (rank, pref) in groups[g_idx].preferences.iter().enumerate().chain([(groups[g_idx].preferences.len(), &String::new())]) {
+                let taken = current.iter()
+                    .enumerate()
+                    .filter(|&(i, &r)| groups[i].preferences.get(r) == Some(pref))
+                    .count();
+                let capacity = projects.iter().find(|p| p.id == *pref).map_or(usize::MAX, |p| p.capacity);
+                if taken < capacity {
+                    current.push(rank);
+                    extend(groups, projects, current, out);
+                    current.pop();
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        extend(groups, projects, &mut Vec::new(), &mut out);
+        out
+    }
+
+    // Wszystkie listy, jakie grupa może zgłosić: uporządkowane podzbiory projektów
+    fn reports(ids: &[String]) -> Vec<Vec<String>> {
+        let mut out: Vec<Vec<String>> = vec![vec![]];
+        for _ in 0..ids.len() {
+            let longer: Vec<Vec<String>> = out.iter()
+                .flat_map(|list| ids.iter()
+                    .filter(|id| !list.contains(id))
+                    .map(|id| [list.as_slice(), std::slice::from_ref(id)].concat())
+                    .collect::<Vec<_>>())
+                .collect();
+            out.extend(longer);
+            out.sort();
+            out.dedup();
+        }
+        out
+    }
+
+    #[test]
+    fn is_pareto_efficient_for_groups() {
+        for seed in 0..150 {
+            for rule in [Priority::Company, Priority::Score] {
+                let (groups, companies, projects) = instance(seed);
+                let results = top_trading_cycles(&groups, &companies, &projects, rule);
+                let ranks: Vec<usize> = groups.iter().map(|g| rank(g, &results)).collect();
+
+                for other in assignments(&groups, &projects) {
+                    let dominates = other.iter().zip(&ranks).all(|(o, r)| o <= r)
+                        && other.iter().zip(&ranks).any(|(o, r)| o < r);
+                    assert!(!dominates, "seed {}: {:?} dominates {:?}", seed, other, ranks);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_group_gains_by_misreporting() {
+        for seed in 0..60 {
+            let (groups, companies, projects) = instance(seed);
+            let ids: Vec<String> = projects.iter().map(|p| p.id.clone()).collect();
+            let truthful = top_trading_cycles(&groups, &companies, &projects, Priority::Company);
+
+            for (g_idx, group) in groups.iter().enumerate() {
+                let honest = rank(group, &truthful);
+                for report in reports(&ids) {
+                    let mut lying = groups.clone();
+                    lying[g_idx].preferences = report;
+                    let results = top_trading_cycles(&lying, &companies, &projects, Priority::Company);
+                    assert!(rank(group, &results) >= honest, "seed {}: {} gains by lying", seed, group.name);
+                }
+            }
+        }
+    }
+}
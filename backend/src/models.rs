@@ -1,19 +1,452 @@
 use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct Group {
     pub name: String,
     pub email: String,
     pub password: String,
     pub preferences: Vec<String>,
+    // Poziom każdej preferencji (równoległy do `preferences`); równe poziomy = remis.
+    // Pusta lista oznacza ścisły porządek.
+    #[serde(default)]
+    pub tiers: Vec<usize>,
+    // Wynik priorytetowy (np. średnia ocen); wyższy = pierwszeństwo w TTC przy `priority=score`
+    #[serde(default)]
+    pub priority: Option<f64>,
+    // Kierunek grupy (np. "bachelor", "master-cs"), do miejsc zarezerwowanych przez firmy
+    #[serde(default)]
+    pub track: Option<String>,
+    // Umiejętności i technologie grupy, porównywane z wymaganiami firm i projektów
+    #[serde(default)]
+    pub skills: Vec<String>,
+    // Rankingi poszczególnych członków; gdy są, `preferences` to ich agregat według `aggregation`
+    #[serde(default)]
+    pub member_rankings: Vec<MemberRanking>,
+    #[serde(default)]
+    pub aggregation: AggregationRule,
+    // Studenci (nazwy kont z /student) tworzący grupę
+    #[serde(default)]
+    pub members: Vec<String>,
+    // Członek, który zaprasza pozostałych
+    #[serde(default)]
+    pub leader: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+// Student zarejestrowany indywidualnie, zanim trafi do grupy
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Student {
+    pub name: String,
+    pub email: String,
+    pub password: String,
+    #[serde(default)]
+    pub skills: Vec<String>,
+    // Nazwy studentów, z którymi chce pracować
+    #[serde(default)]
+    pub want_with: Vec<String>,
+    // Okresy, w których student nie może być na rozmowie
+    #[serde(default)]
+    pub unavailable: Vec<TimeRange>,
+}
+
+// Publiczny widok studenta (GET /student/list), bez hasła
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StudentProfile {
+    pub name: String,
+    pub email: String,
+    pub skills: Vec<String>,
+    pub want_with: Vec<String>,
+}
+
+// Czas lokalny w formacie "RRRR-MM-DDTGG:MM" (jak z pola datetime-local, bez strefy)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TimeRange {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetAvailability {
+    pub session_id: String,
+    pub unavailable: Vec<TimeRange>,
+}
+
+// Termin rozmowy opublikowany przez firmę; `booked_by` to grupa, która go zarezerwowała
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InterviewSlot {
+    pub id: String,
+    pub company: String,
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub booked_by: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AddSlot {
+    pub session_id: String,
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SlotRequest {
+    pub session_id: String,
+    pub slot_id: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SlotParams {
+    pub company: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BookingResponse {
+    pub ok: bool,
+    pub message: String,
+    pub slot: Option<InterviewSlot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UpdateStudent {
+    pub session_id: String,
+    pub skills: Vec<String>,
+    pub want_with: Vec<String>,
+}
+
+// Grupa zakładana przez studenta, który zostaje jej liderem
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateTeam {
+    pub session_id: String,
+    pub name: String,
+}
+
+// Zaproszenie do grupy dla adresu e-mail, przyjmowane tokenem
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Invitation {
+    pub token: String,
+    pub group: String,
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InviteMember {
+    pub session_id: String,
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InvitationResponse {
+    pub session_id: String,
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionRequest {
+    pub session_id: String,
+}
+
+// Dozwolony rozmiar grupy; poza nim grupa nie może zgłaszać preferencji
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GroupSizeLimits {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetGroupSize {
+    pub admin_key: Option<String>,
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProposeTeams {
+    pub admin_key: Option<String>,
+    pub size: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProposedTeam {
+    pub members: Vec<String>,
+    pub skills: Vec<String>,
+    // Życzenia "chcę pracować z" spełnione w zespole
+    pub wishes_met: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TeamProposal {
+    pub size: usize,
+    pub teams: Vec<ProposedTeam>,
+    // Wzajemne życzenia, których nie dało się spełnić
+    pub split_pairs: Vec<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MemberRanking {
+    pub member: String,
+    pub preferences: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationRule {
+    #[default]
+    Borda,
+    Copeland,
+    KemenyYoung,
+}
+
+// Ranking zalogowanego członka grupy; pusta lista `preferences` go usuwa
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SubmitRanking {
+    pub session_id: String,
+    pub preferences: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetAggregation {
+    pub session_id: String,
+    pub rule: AggregationRule,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MemberAgreement {
+    pub member: String,
+    pub preferences: Vec<String>,
+    // Od 0 (lista grupy odwraca każdą parę członka) do 1 (pełna zgoda)
+    pub agreement: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AggregationReport {
+    pub rule: AggregationRule,
+    pub preferences: Vec<String>,
+    pub tiers: Vec<usize>,
+    pub members: Vec<MemberAgreement>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct Company {
     pub name: String,
     pub email: String,
     pub password: String,
     pub preferences: Vec<String>,
+    // Poziom każdej preferencji (równoległy do `preferences`); równe poziomy = remis.
+    // Pusta lista oznacza ścisły porządek.
+    #[serde(default)]
+    pub tiers: Vec<usize>,
+    // Minimalna liczba grup dla firmy (np. sponsora) w trybie lower_quotas
+    #[serde(default)]
+    pub min_groups: usize,
+    // Miejsca zarezerwowane dla grup z danego kierunku (algorithm=track_reserves)
+    #[serde(default)]
+    pub reserved: Vec<TrackReservation>,
+    // Umiejętności wymagane we wszystkich projektach firmy; grupa bez nich jest dla firmy nieakceptowalna
+    #[serde(default)]
+    pub required_skills: Vec<String>,
+    // Kryteria oceny grup; gdy są oceny, `preferences` wynika z ważonych średnich
+    #[serde(default)]
+    pub rubric: Vec<Criterion>,
+    #[serde(default)]
+    pub reviews: Vec<Review>,
+    // Lista ułożona ręcznie, przechowywana osobno, dopóki `preferences` wynika z ocen
+    #[serde(default)]
+    pub manual: Option<RankedList>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RankedList {
+    pub preferences: Vec<String>,
+    #[serde(default)]
+    pub tiers: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Criterion {
+    pub name: String,
+    pub weight: f64,
+    // Najwyższa możliwa ocena (skala od 0)
+    pub max: f64,
+}
+
+// Oceny jednej grupy wystawione przez jednego recenzenta, po nazwie kryterium
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Review {
+    pub reviewer: String,
+    pub group: String,
+    pub scores: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetRubric {
+    pub session_id: String,
+    pub criteria: Vec<Criterion>,
+}
+
+// Puste `scores` usuwa ocenę recenzenta
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SubmitReview {
+    pub session_id: String,
+    pub reviewer: String,
+    pub group: String,
+    pub scores: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CriterionAverage {
+    pub criterion: String,
+    pub average: Option<f64>,
+    pub reviewers: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GroupScore {
+    pub group: String,
+    // Ważona średnia znormalizowanych ocen, od 0 do 1
+    pub total: f64,
+    pub criteria: Vec<CriterionAverage>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RubricReport {
+    pub company: String,
+    pub criteria: Vec<Criterion>,
+    pub scores: Vec<GroupScore>,
+    pub preferences: Vec<String>,
+    pub tiers: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TrackReservation {
+    pub track: String,
+    pub seats: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReserveSeats {
+    pub session_id: String,
+    pub track: String,
+    pub seats: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetTrack {
+    pub admin_key: Option<String>,
+    pub group: String,
+    pub track: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Project {
+    pub id: String,
+    pub company: String,
+    pub title: String,
+    pub description: String,
+    pub capacity: usize,
+    #[serde(default)]
+    pub min_capacity: usize,
+    #[serde(default)]
+    pub required_skills: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetSkills {
+    pub session_id: String,
+    pub skills: Vec<String>,
+    // Tylko dla firm: wymagania jednego projektu zamiast całej firmy
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EligibilityParams {
+    pub company: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MissingSkills {
+    pub project: String,
+    pub project_title: String,
+    pub skills: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IneligibleGroup {
+    pub group: String,
+    // Grupa pasuje do innego projektu tej firmy
+    pub eligible_elsewhere: bool,
+    pub missing: Vec<MissingSkills>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EligibilityReport {
+    pub company: String,
+    pub required_skills: Vec<String>,
+    pub eligible: Vec<String>,
+    pub ineligible: Vec<IneligibleGroup>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplicationStatus {
+    #[default]
+    Pending,
+    Shortlisted,
+    Rejected,
+}
+
+// Zgłoszenie grupy do firmy (bez `project` do wszystkich jej projektów) z krótką motywacją
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Application {
+    pub group: String,
+    pub company: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    pub motivation: String,
+    #[serde(default)]
+    pub status: ApplicationStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Apply {
+    pub session_id: String,
+    pub company: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    pub motivation: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WithdrawApplication {
+    pub session_id: String,
+    pub company: String,
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReviewApplication {
+    pub session_id: String,
+    pub group: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    pub status: ApplicationStatus,
+}
+
+// Gdy `required`, do skojarzenia trafiają tylko pary z zakwalifikowanych zgłoszeń
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetApplicationPhase {
+    pub admin_key: Option<String>,
+    pub required: bool,
+}
+
+// Rejestracja grupy; pozostałe pola (preferencje, członkowie, lider) ustawia się osobnymi endpointami
+#[derive(Deserialize)]
+pub struct RegisterGroup {
+    pub name: String,
+    pub email: String,
+    pub password: String,
 }
 
 #[derive(Deserialize)]
@@ -35,10 +468,453 @@ pub struct LoginResponse {
 pub struct AddPref {
     pub session_id: String,
     pub pref: String,
+    #[serde(default)]
+    pub tie_with_previous: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetPrefs {
+    pub session_id: String,
+    pub tiers: Vec<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SetPriority {
+    pub admin_key: Option<String>,
+    pub group: String,
+    pub priority: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AddProject {
+    pub session_id: String,
+    pub title: String,
+    pub description: String,
+    pub capacity: usize,
+    #[serde(default)]
+    pub min_capacity: usize,
+    #[serde(default)]
+    pub required_skills: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Proposer {
+    #[default]
+    Groups,
+    Companies,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    #[default]
+    GaleShapley,
+    Egalitarian,
+    MinimumRegret,
+    RankMaximal,
+    MinCost,
+    SerialDictatorship,
+    TopTradingCycles,
+    MinimalDisruption,
+    LowerQuotas,
+    TrackReserves,
+}
+
+// Skąd firma bierze pierwszeństwo grup w Top Trading Cycles
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    // Lista firmy, potem grupy spoza listy; remisy rozstrzyga wynik priorytetowy
+    #[default]
+    Company,
+    // Sam wynik priorytetowy grupy (np. średnia ocen), wspólny dla wszystkich firm
+    Score,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    #[default]
+    ListOrder,
+    Random,
+    Lottery,
+    SuperStable,
+    StronglyStable,
+}
+
+// Opis parametru zapytania /match; puste `values` = dowolna liczba
 #[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AlgorithmParameter {
+    pub name: String,
+    pub description: String,
+    pub values: Vec<String>,
+    pub default: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AlgorithmInfo {
+    pub algorithm: Algorithm,
+    pub description: String,
+    pub parameters: Vec<AlgorithmParameter>,
+    // Czy wynik jest zawsze stabilny
+    #[serde(default)]
+    pub stable: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AlgorithmCatalog {
+    pub algorithms: Vec<AlgorithmInfo>,
+    pub common_parameters: Vec<AlgorithmParameter>,
+}
+
+// Co zrobić z grupami, które algorytm zostawił bez pary
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmatchedPolicy {
+    #[default]
+    Leave,
+    FreeAcceptable,
+    Overflow,
+    Random,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MatchParams {
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    #[serde(default)]
+    pub proposer: Proposer,
+    #[serde(default)]
+    pub ties: TieBreak,
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub unmatched: UnmatchedPolicy,
+    pub overflow_limit: Option<usize>,
+    #[serde(default)]
+    pub priority: Priority,
+    // Przebieg, od którego liczymy zmiany; domyślnie opublikowany
+    pub base_run: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct MatchResult {
     pub group: String,
     pub company: String,
+    pub project: String,
+    pub project_title: String,
+    // Pozycje (od 1) na liście grupy i firmy; None = para spoza listy
+    #[serde(default)]
+    pub group_rank: Option<usize>,
+    #[serde(default)]
+    pub company_rank: Option<usize>,
+    #[serde(default)]
+    pub mutually_acceptable: bool,
+    #[serde(default)]
+    pub fallback: bool,
+    // Para przypięta przez administratora
+    #[serde(default)]
+    pub pinned: bool,
+    // Dlaczego grupa nie dostała żadnego z wyżej ocenionych projektów
+    #[serde(default)]
+    pub explanations: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MatchResponse {
+    pub algorithm: Algorithm,
+    pub proposer: Proposer,
+    pub ties: TieBreak,
+    pub seed: Option<u64>,
+    // Zapisany przebieg (GET /match/run?run_id=...)
+    pub run_id: Option<String>,
+    pub unmatched_policy: UnmatchedPolicy,
+    pub unmatched: Vec<String>,
+    pub tie_report: Option<TieReport>,
+    pub comparison: Option<Vec<RankProfile>>,
+    pub results: Vec<MatchResult>,
+    // Oficjalny, opublikowany wynik (false = podgląd)
+    #[serde(default)]
+    pub published: bool,
+    #[serde(default)]
+    pub rematch: Option<RematchReport>,
+    #[serde(default)]
+    pub constraints: Option<ConstraintReport>,
+    #[serde(default)]
+    pub quotas: Option<QuotaReport>,
+    #[serde(default)]
+    pub reservations: Option<ReservationReport>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReservedSeats {
+    pub company: String,
+    pub track: String,
+    pub reserved: usize,
+    pub filled: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReservationReport {
+    pub seats: Vec<ReservedSeats>,
+    pub blocking_pairs: usize,
+}
+
+// Minimum, którego nie dało się spełnić, i ile da się osiągnąć (`project` = None: minimum całej firmy)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QuotaRelaxation {
+    pub company: String,
+    pub project: Option<String>,
+    pub minimum: usize,
+    pub achievable: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QuotaReport {
+    pub feasible: bool,
+    pub relaxations: Vec<QuotaRelaxation>,
+    pub blocking_pairs: usize,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SetQuota {
+    pub admin_key: Option<String>,
+    pub company: String,
+    pub project: Option<String>,
+    pub minimum: usize,
+}
+
+// Para wskazana przez administratora; `project` tylko przy przypięciu do konkretnego projektu
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PairConstraint {
+    pub group: String,
+    pub company: String,
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PairConstraints {
+    pub pinned: Vec<PairConstraint>,
+    pub forbidden: Vec<PairConstraint>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ConstraintRequest {
+    pub admin_key: Option<String>,
+    pub group: String,
+    pub company: String,
+    pub project: Option<String>,
+}
+
+// Jak ograniczenia wpłynęły na przebieg; pary blokujące liczone względem oryginalnych list
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConstraintReport {
+    pub applied: Vec<String>,
+    pub blocking_pairs: Vec<BlockingPair>,
+}
+
+// Zmiany względem przebiegu bazowego przy ponownym dopasowaniu
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RematchReport {
+    pub base_run: String,
+    // Pary z przebiegu bazowego, które zostały bez zmian
+    pub kept: Vec<MatchResult>,
+    pub changed: Vec<GroupMove>,
+}
+
+// Listy jednej strony w chwili przebiegu
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ListSnapshot {
+    pub name: String,
+    pub preferences: Vec<String>,
+    #[serde(default)]
+    pub tiers: Vec<usize>,
+    #[serde(default)]
+    pub priority: Option<f64>,
+    #[serde(default)]
+    pub min_groups: usize,
+    #[serde(default)]
+    pub track: Option<String>,
+    #[serde(default)]
+    pub reserved: Vec<TrackReservation>,
+    #[serde(default)]
+    pub skills: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct InputSnapshot {
+    pub groups: Vec<ListSnapshot>,
+    pub companies: Vec<ListSnapshot>,
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub constraints: PairConstraints,
+    // Zgłoszenia i tryb shortlisty; przy `applications_required` dopasowanie obejmuje tylko pary z listy
+    #[serde(default)]
+    pub applications: Vec<Application>,
+    #[serde(default)]
+    pub applications_required: bool,
+}
+
+// Zapisany przebieg /match; parametry zawierają użyty seed, więc przebieg da się odtworzyć
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MatchRun {
+    pub id: String,
+    // Sekundy od epoki Unix
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    pub params: MatchParams,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub input_hash: String,
+    #[serde(default)]
+    pub input: InputSnapshot,
+    #[serde(default)]
+    pub results: Vec<MatchResult>,
+    #[serde(default)]
+    pub unmatched: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunSummary {
+    pub id: String,
+    pub created_at: u64,
+    pub algorithm: Algorithm,
+    pub params: MatchParams,
+    pub seed: Option<u64>,
+    pub input_hash: String,
+    pub matched: usize,
+    pub unmatched: usize,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RunParams {
+    pub run_id: String,
+}
+
+// Podgląd zamiast opublikowanego wyniku i zapis przebiegu są tylko dla administratora
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct PreviewParams {
+    #[serde(default)]
+    pub preview: bool,
+    #[serde(default)]
+    pub record: bool,
+    pub admin_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Publication {
+    pub run_id: String,
+    pub published_at: u64,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct PublishRun {
+    pub admin_key: Option<String>,
+    pub run_id: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct AdminRequest {
+    pub admin_key: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DiffParams {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PreferenceChange {
+    // "group" albo "company"
+    pub side: String,
+    pub name: String,
+    pub before: Option<Vec<String>>,
+    pub after: Option<Vec<String>>,
+    // Zmienione pola listy (np. "preferences", "tiers", "skills"); puste, gdy listę dodano albo usunięto
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GroupMove {
+    pub group: String,
+    pub from_project: Option<String>,
+    pub to_project: Option<String>,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunDiff {
+    pub from: String,
+    pub to: String,
+    pub same_input: bool,
+    pub parameter_changes: Vec<String>,
+    pub preference_changes: Vec<PreferenceChange>,
+    pub moved: Vec<GroupMove>,
+}
+
+// group_ranks[k] = liczba grup, które dostały swój (k + 1)-szy wybór (analogicznie dla firm)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RankProfile {
+    pub algorithm: Algorithm,
+    pub group_ranks: Vec<usize>,
+    pub company_ranks: Vec<usize>,
+    pub outside_preferences: usize,
+    pub unmatched: usize,
+    pub blocking_pairs: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BlockingPair {
+    pub group: String,
+    pub company: String,
+    pub project: String,
+    pub group_reason: String,
+    pub company_reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Violation {
+    pub group: String,
+    pub company: String,
+    pub project: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StabilityReport {
+    pub stable: bool,
+    pub blocking_pairs: Vec<BlockingPair>,
+    pub violations: Vec<Violation>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TieReport {
+    pub exists: bool,
+    pub message: String,
+    pub blocking_pairs: Vec<BlockingPair>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StableMatching {
+    pub group_rank_sum: usize,
+    pub company_rank_sum: usize,
+    pub results: Vec<MatchResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LatticeReport {
+    pub truncated: bool,
+    pub matchings: Vec<StableMatching>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct LatticeParams {
+    pub limit: Option<usize>,
+    pub admin_key: Option<String>,
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 use axum::{
     Json,
-    extract::{Query, State}
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
 };
 use std::{
     collections::HashMap,
@@ -9,27 +11,37 @@ use std::{
 use uuid::Uuid;
 
 use crate::{
-    models::{Group, Company, LoginRequest, LoginResponse, MatchResult, AddPref},
+    models::{Group, Company, Project, RegisterGroup, LoginRequest, LoginResponse, MatchParams, MatchResponse, MatchRun, RunParams, RunSummary, DiffParams, RunDiff, PreviewParams, Publication, PublishRun, AdminRequest, PairConstraint, PairConstraints, ConstraintRequest, AlgorithmCatalog, SetPriority, SetQuota, SetTrack, ReserveSeats, TrackReservation, SetSkills, EligibilityParams, EligibilityReport, SubmitRanking, SetAggregation, AggregationReport, MemberRanking, Student, StudentProfile, UpdateStudent, ProposeTeams, TeamProposal, CreateTeam, Invitation, InviteMember, InvitationResponse, SessionRequest, GroupSizeLimits, SetGroupSize, Application, ApplicationStatus, Apply, WithdrawApplication, ReviewApplication, SetApplicationPhase, Criterion, Review, SetRubric, SubmitReview, RubricReport, TimeRange, SetAvailability, InterviewSlot, AddSlot, SlotRequest, SlotParams, BookingResponse, StabilityReport, LatticeParams, LatticeReport, AddPref, SetPrefs, AddProject},
     state::AppState,
-    matching::stable_matching,
+    aggregation::{apply, aggregation_report},
+    teams::propose_teams,
+    rubric::{derive_preferences, rubric_report},
+    schedule::{parse_time, format_time, time_range, company_busy, booking_conflict, ics},
+    history::{record_run, restore, summary, diff_runs, published_run, published_response, now},
+    matching::{verify_constrained, constrained_lattice, company_projects, run_matching, algorithm_catalog, is_forbidden, eligibility_report, shortlisted_lists},
 };
 
 pub async fn add_group(
     State(state): State<Arc<Mutex<AppState>>>,
-    Json(group): Json<Group>
+    Json(body): Json<RegisterGroup>
 ) -> Json<LoginResponse> {
     let mut s = state.lock().unwrap();
-    if s.groups.iter().any(|g| g.email == group.email) {
+    if s.email_in_use(&body.email) {
         return Json(LoginResponse {
             ok: false,
-            message: "Group with this email already exists".into(),
+            message: "This email is already in use".into(),
             session_id: None,
             email: None,
             role: None,
         });
     }
 
-    s.groups.push(group);
+    s.groups.push(Group {
+        name: body.name,
+        email: body.email,
+        password: body.password,
+        ..Default::default()
+    });
     let _ = s.save();
 
     Json(LoginResponse {
@@ -46,10 +58,10 @@ pub async fn add_company(
     Json(company): Json<Company>
 ) -> Json<LoginResponse> {
     let mut s = state.lock().unwrap();
-    if s.companies.iter().any(|c| c.email == company.email) {
+    if s.email_in_use(&company.email) {
         return Json(LoginResponse {
             ok: false,
-            message: "Company with this email already exists".into(),
+            message: "This email is already in use".into(),
             session_id: None,
             email: None,
             role: None,
@@ -68,11 +80,1086 @@ pub async fn add_company(
     })
 }
 
+// Przebieg bazowy: wskazany w parametrach albo opublikowany
+fn base_run<'a>(s: &'a AppState, params: &MatchParams) -> Option<&'a MatchRun> {
+    let id = params.base_run.as_ref().or(s.published.as_ref().map(|p| &p.run_id))?;
+    s.runs.iter().find(|r| &r.id == id)
+}
+
 pub async fn match_groups(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<MatchParams>,
+    Query(preview): Query<PreviewParams>,
+) -> Json<Option<MatchResponse>> {
+    let mut s = state.lock().unwrap();
+    let admin = is_admin(preview.admin_key.as_deref());
+    if let Some(response) = published_response(&s).filter(|_| !(preview.preview && admin)) {
+        return Json(Some(response));
+    }
+
+    let base = base_run(&s, &params);
+    let params = MatchParams { base_run: base.map(|r| r.id.clone()), ..params };
+    let (groups, companies) = matching_lists(&s);
+    let Some(mut response) = run_matching(&groups, &companies, &s.projects, &params, base, &s.constraints, shortlist(&s)) else {
+        return Json(None);
+    };
+    // Zwykłe GET /match niczego nie zapisuje; przebieg trafia do historii tylko na żądanie administratora
+    if preview.record && admin && record_run(&mut s, &params, &mut response) {
+        let _ = s.save();
+    }
+    Json(Some(response))
+}
+
+// Przebiegi widzi administrator; pozostali tylko opublikowany
+fn visible_runs<'a>(s: &'a AppState, admin_key: Option<&str>) -> Vec<&'a MatchRun> {
+    let admin = is_admin(admin_key);
+    let published = s.published.as_ref().map(|p| &p.run_id);
+    s.runs.iter().filter(|r| admin || Some(&r.id) == published).collect()
+}
+
+pub async fn replay_match(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<RunParams>,
+    Query(admin): Query<AdminRequest>,
+) -> Json<Option<MatchResponse>> {
+    let s = state.lock().unwrap();
+    let runs = visible_runs(&s, admin.admin_key.as_deref());
+    let Some(run) = runs.iter().find(|r| r.id == params.run_id) else {
+        return Json(None);
+    };
+
+    let base = run.params.base_run.as_ref().and_then(|id| s.runs.iter().find(|r| &r.id == id));
+    let (groups, companies) = restore(&run.input);
+    let shortlist = run.input.applications_required.then_some(&run.input.applications[..]);
+    let Some(mut response) = run_matching(&groups, &companies, &run.input.projects, &run.params, base, &run.input.constraints, shortlist) else {
+        return Json(None);
+    };
+    response.run_id = Some(run.id.clone());
+    Json(Some(response))
+}
+
+pub async fn list_runs(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(admin): Query<AdminRequest>,
+) -> Json<Vec<RunSummary>> {
+    let s = state.lock().unwrap();
+    Json(visible_runs(&s, admin.admin_key.as_deref()).into_iter().map(summary).collect())
+}
+
+pub async fn get_run(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<RunParams>,
+    Query(admin): Query<AdminRequest>,
+) -> Json<Option<MatchRun>> {
+    let s = state.lock().unwrap();
+    let runs = visible_runs(&s, admin.admin_key.as_deref());
+    Json(runs.into_iter().find(|r| r.id == params.run_id).cloned())
+}
+
+pub async fn diff_match_runs(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<DiffParams>,
+    Query(admin): Query<AdminRequest>,
+) -> Json<Option<RunDiff>> {
+    let s = state.lock().unwrap();
+    let runs = visible_runs(&s, admin.admin_key.as_deref());
+    let find = |id: &str| runs.iter().find(|r| r.id == id);
+    let (Some(from), Some(to)) = (find(&params.from), find(&params.to)) else {
+        return Json(None);
+    };
+    Json(Some(diff_runs(from, to)))
+}
+
+pub async fn list_algorithms() -> Json<AlgorithmCatalog> {
+    Json(algorithm_catalog())
+}
+
+pub async fn verify_match(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<MatchParams>,
+    Query(admin): Query<AdminRequest>,
+) -> Json<Option<StabilityReport>> {
+    let s = state.lock().unwrap();
+
+    // Po publikacji sprawdzany jest opublikowany wynik na danych, na których powstał, chyba że pyta administrator
+    if let Some(run) = published_run(&s).filter(|_| !is_admin(admin.admin_key.as_deref())) {
+        let (groups, companies) = restore(&run.input);
+        return Json(Some(verify_constrained(&groups, &companies, &run.input.projects, &run.input.constraints, &run.results)));
+    }
+
+    let (groups, companies) = matching_lists(&s);
+    let Some(response) = run_matching(&groups, &companies, &s.projects, &params, base_run(&s, &params), &s.constraints, shortlist(&s)) else {
+        return Json(None);
+    };
+    Json(Some(verify_constrained(&groups, &companies, &s.projects, &s.constraints, &response.results)))
+}
+
+// W fazie zgłoszeń skojarzenie widzi tylko pary z zakwalifikowanych zgłoszeń
+fn matching_lists(s: &AppState) -> (Vec<Group>, Vec<Company>) {
+    if s.applications_required {
+        shortlisted_lists(&s.groups, &s.companies, &s.projects, &s.applications)
+    } else {
+        (s.groups.clone(), s.companies.clone())
+    }
+}
+
+fn shortlist(s: &AppState) -> Option<&[Application]> {
+    s.applications_required.then_some(&s.applications[..])
+}
+
+// Jeśli ADMIN_KEY nie jest ustawiony, endpointy administracyjne są otwarte (tryb deweloperski)
+fn is_admin(admin_key: Option<&str>) -> bool {
+    match std::env::var("ADMIN_KEY") {
+        Ok(expected) => admin_key == Some(expected.as_str()),
+        Err(_) => true,
+    }
+}
+
+pub async fn admin_match_lattice(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<LatticeParams>,
+) -> Json<Option<LatticeReport>> {
+    if !is_admin(params.admin_key.as_deref()) {
+        return Json(None);
+    }
+
+    let limit = params.limit.unwrap_or(100).min(10_000);
+    let s = state.lock().unwrap();
+    let (groups, companies) = matching_lists(&s);
+    Json(Some(constrained_lattice(&groups, &companies, &s.projects, &s.constraints, limit)))
+}
+
+pub async fn admin_publish_run(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<PublishRun>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    if !s.runs.iter().any(|r| r.id == req.run_id) {
+        return Json(false);
+    }
+    s.published = Some(Publication {
+        run_id: req.run_id,
+        published_at: now(),
+    });
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn admin_unpublish(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<AdminRequest>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    s.published = None;
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn admin_constraints(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(req): Query<AdminRequest>,
+) -> Json<Option<PairConstraints>> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(None);
+    }
+
+    let s = state.lock().unwrap();
+    Json(Some(s.constraints.clone()))
+}
+
+// Para musi istnieć, a przypięty projekt należeć do firmy
+fn valid_pair(s: &AppState, req: &ConstraintRequest) -> bool {
+    s.groups.iter().any(|g| g.name == req.group)
+        && s.companies.iter().any(|c| c.name == req.company)
+        && req.project.as_ref().is_none_or(|id| company_projects(&s.companies, &s.projects).iter()
+            .any(|p| &p.id == id && p.company == req.company))
+}
+
+pub async fn admin_pin_pair(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<ConstraintRequest>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    if !valid_pair(&s, &req) || is_forbidden(&s.constraints.forbidden, &req.group, &req.company) {
+        return Json(false);
+    }
+
+    // Grupa może być przypięta tylko do jednej firmy
+    s.constraints.pinned.retain(|p| p.group != req.group);
+    s.constraints.pinned.push(PairConstraint {
+        group: req.group,
+        company: req.company,
+        project: req.project,
+    });
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn admin_forbid_pair(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<ConstraintRequest>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    let pinned = s.constraints.pinned.iter().any(|p| p.group == req.group && p.company == req.company);
+    if !valid_pair(&s, &req) || pinned {
+        return Json(false);
+    }
+
+    if !is_forbidden(&s.constraints.forbidden, &req.group, &req.company) {
+        s.constraints.forbidden.push(PairConstraint {
+            group: req.group,
+            company: req.company,
+            project: None,
+        });
+        let _ = s.save();
+    }
+    Json(true)
+}
+
+pub async fn admin_remove_constraint(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<ConstraintRequest>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    let before = s.constraints.pinned.len() + s.constraints.forbidden.len();
+    let other = |p: &PairConstraint| p.group != req.group || p.company != req.company;
+    s.constraints.pinned.retain(other);
+    s.constraints.forbidden.retain(other);
+
+    let removed = s.constraints.pinned.len() + s.constraints.forbidden.len() < before;
+    if removed {
+        let _ = s.save();
+    }
+    Json(removed)
+}
+
+pub async fn admin_set_priority(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<SetPriority>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    let Some(group) = s.groups.iter_mut().find(|g| g.name == req.group) else {
+        return Json(false);
+    };
+    group.priority = req.priority;
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn admin_set_track(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<SetTrack>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    let Some(group) = s.groups.iter_mut().find(|g| g.name == req.group) else {
+        return Json(false);
+    };
+    group.track = req.track.filter(|t| !t.trim().is_empty());
+    let _ = s.save();
+    Json(true)
+}
+
+// Minimum grup dla firmy albo (gdy podano `project`) dla jednego z jej projektów
+pub async fn admin_set_quota(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<SetQuota>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    match &req.project {
+        Some(id) => {
+            let Some(project) = s.projects.iter_mut().find(|p| &p.id == id && p.company == req.company) else {
+                return Json(false);
+            };
+            if req.minimum > project.capacity {
+                return Json(false);
+            }
+            project.min_capacity = req.minimum;
+        }
+        None => {
+            let Some(company) = s.companies.iter_mut().find(|c| c.name == req.company) else {
+                return Json(false);
+            };
+            company.min_groups = req.minimum;
+        }
+    }
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn add_student(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(student): Json<Student>
+) -> Json<LoginResponse> {
+    let mut s = state.lock().unwrap();
+    let taken = s.email_in_use(&student.email) || s.students.iter().any(|x| x.name == student.name);
+    if taken || student.name.trim().is_empty() {
+        return Json(LoginResponse {
+            ok: false,
+            message: "Student with this name or email already exists".into(),
+            session_id: None,
+            email: None,
+            role: None,
+        });
+    }
+
+    let student = Student {
+        skills: clean_skills(student.skills),
+        want_with: clean_skills(student.want_with),
+        unavailable: normalize_ranges(student.unavailable).unwrap_or_default(),
+        ..student
+    };
+    s.students.push(student);
+    let _ = s.save();
+
+    Json(LoginResponse {
+        ok: true,
+        message: "Student registered successfully".into(),
+        session_id: None,
+        email: None,
+        role: None,
+    })
+}
+
+pub async fn login_student(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(login): Json<LoginRequest>
+) -> Json<LoginResponse> {
+    let mut s = state.lock().unwrap();
+
+    let found = s.students.iter().find(|x|
+        x.email == login.email && x.password == login.password
+    );
+
+    if found.is_some() {
+        let session_id = Uuid::new_v4().to_string();
+        s.sessions.insert(session_id.clone(), login.email.clone());
+        let _ = s.save();
+
+        Json(LoginResponse {
+            ok: true,
+            message: "Student login success".into(),
+            session_id: Some(session_id),
+            email: Some(login.email),
+            role: Some("student".into()),
+        })
+    } else {
+        Json(LoginResponse {
+            ok: false,
+            message: "Invalid credentials".into(),
+            session_id: None,
+            email: None,
+            role: None,
+        })
+    }
+}
+
+pub async fn student_me(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Option<Student>> {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+
+    let s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&session) {
+        Some(e) => e.clone(),
+        None => return Json(None),
+    };
+
+    Json(s.students.iter().find(|x| x.email == email).cloned())
+}
+
+// Umiejętności i życzenia "chcę pracować z" (tylko istniejący studenci, bez siebie)
+pub async fn student_update(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<UpdateStudent>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&body.session_id) {
+        Some(e) => e.clone(),
+        None => return Json(false),
+    };
+
+    let names: Vec<String> = s.students.iter().map(|x| x.name.clone()).collect();
+    let Some(student) = s.students.iter_mut().find(|x| x.email == email) else {
+        return Json(false);
+    };
+
+    student.skills = clean_skills(body.skills);
+    student.want_with = clean_skills(body.want_with).into_iter()
+        .filter(|w| names.contains(w) && *w != student.name)
+        .collect();
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn list_students(
     State(state): State<Arc<Mutex<AppState>>>
-) -> Json<Vec<MatchResult>> {
+) -> Json<Vec<StudentProfile>> {
+    let s = state.lock().unwrap();
+    Json(s.students.iter()
+        .map(|x| StudentProfile {
+            name: x.name.clone(),
+            email: x.email.clone(),
+            skills: x.skills.clone(),
+            want_with: x.want_with.clone(),
+        })
+        .collect())
+}
+
+// Studenci, którzy nie należą jeszcze do żadnej grupy
+fn unassigned_students(s: &AppState) -> Vec<Student> {
+    s.students.iter()
+        .filter(|x| !s.groups.iter().any(|g| g.members.contains(&x.name)))
+        .cloned()
+        .collect()
+}
+
+pub async fn admin_propose_teams(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<ProposeTeams>,
+) -> Json<Option<TeamProposal>> {
+    if !is_admin(req.admin_key.as_deref()) || req.size == 0 {
+        return Json(None);
+    }
+
+    let mut s = state.lock().unwrap();
+    if s.group_size.max.is_some_and(|max| req.size > max) {
+        return Json(None);
+    }
+    let proposal = propose_teams(&unassigned_students(&s), req.size);
+    s.team_proposal = Some(proposal.clone());
+    let _ = s.save();
+    Json(Some(proposal))
+}
+
+pub async fn admin_team_proposal(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(req): Query<AdminRequest>,
+) -> Json<Option<TeamProposal>> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(None);
+    }
+
+    let s = state.lock().unwrap();
+    Json(s.team_proposal.clone())
+}
+
+// Zakłada grupy z ostatniej propozycji (wszystkie albo żadnej). Grupa nie ma własnego konta,
+// członkowie działają za nią swoimi sesjami, a jej umiejętności to suma umiejętności członków.
+pub async fn admin_accept_teams(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<AdminRequest>,
+) -> Json<Option<Vec<String>>> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(None);
+    }
+
+    let mut s = state.lock().unwrap();
+    let Some(proposal) = s.team_proposal.clone() else {
+        return Json(None);
+    };
+
+    let free = unassigned_students(&s);
+    let mut created: Vec<Group> = Vec::new();
+    for team in &proposal.teams {
+        let Some(members) = team.members.iter()
+            .map(|name| free.iter().find(|x| &x.name == name))
+            .collect::<Option<Vec<&Student>>>()
+        else {
+            return Json(None);
+        };
+        if members.is_empty() {
+            continue;
+        }
+
+        let name = (1..)
+            .map(|k| format!("team-{}", k))
+            .find(|name| !s.groups.iter().chain(&created).any(|g| &g.name == name))
+            .unwrap();
+        let group = Group {
+            name,
+            skills: team.skills.clone(),
+            members: team.members.clone(),
+            leader: team.members.first().cloned(),
+            ..Default::default()
+        };
+        // Zespół poza limitami rozmiaru nie mógłby zgłaszać preferencji
+        if !size_allowed(&s.group_size, &group) {
+            return Json(None);
+        }
+        created.push(group);
+    }
+
+    let names = created.iter().map(|g| g.name.clone()).collect();
+    s.groups.extend(created);
+    s.team_proposal = None;
+    let _ = s.save();
+    Json(Some(names))
+}
+
+pub async fn admin_set_group_size(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<SetGroupSize>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) || req.max.is_some_and(|max| max < req.min.max(1)) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    s.group_size = GroupSizeLimits { min: req.min, max: req.max };
+    let _ = s.save();
+    Json(true)
+}
+
+fn session_student(s: &AppState, session: &str) -> Option<usize> {
+    let email = s.sessions.get(session)?;
+    s.students.iter().position(|x| &x.email == email)
+}
+
+// Grupa zalogowana własnym kontem albo kontem jednego z członków
+fn session_group(s: &AppState, session: &str) -> Option<usize> {
+    let email = s.sessions.get(session)?;
+    if let Some(idx) = session_student(s, session) {
+        return group_of_student(s, &s.students[idx].name);
+    }
+    s.groups.iter().position(|g| !g.email.is_empty() && &g.email == email)
+}
+
+fn group_of_student(s: &AppState, name: &str) -> Option<usize> {
+    s.groups.iter().position(|g| g.members.iter().any(|m| m == name))
+}
+
+// Czy liczba członków mieści się w limitach ustawionych przez administratora
+fn size_allowed(limits: &GroupSizeLimits, group: &Group) -> bool {
+    group.members.len() >= limits.min && limits.max.is_none_or(|max| group.members.len() <= max)
+}
+
+// Student spoza grup zakłada grupę i zostaje jej liderem; grupa nie ma własnego konta
+pub async fn student_create_group(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<CreateTeam>
+) -> Json<Option<Group>> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_student(&s, &body.session_id) else {
+        return Json(None);
+    };
+    let student = s.students[idx].clone();
+    let name = body.name.trim().to_string();
+    let taken = s.groups.iter().any(|g| g.name == name);
+    if name.is_empty() || taken || group_of_student(&s, &student.name).is_some() {
+        return Json(None);
+    }
+
+    let group = Group {
+        name,
+        skills: student.skills,
+        members: vec![student.name.clone()],
+        leader: Some(student.name),
+        ..Default::default()
+    };
+    s.groups.push(group.clone());
+    let _ = s.save();
+    Json(Some(group))
+}
+
+// Lider zaprasza studenta po adresie e-mail; ponowne zaproszenie zwraca istniejący token
+pub async fn group_invite(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<InviteMember>
+) -> Json<Option<Invitation>> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_student(&s, &body.session_id) else {
+        return Json(None);
+    };
+    let leader = s.students[idx].name.clone();
+    let Some(g_idx) = s.groups.iter().position(|g| g.leader.as_ref() == Some(&leader)) else {
+        return Json(None);
+    };
+    let group = &s.groups[g_idx];
+    let email = body.email.trim().to_string();
+    let member = s.students.iter().any(|x| x.email == email && group.members.contains(&x.name));
+    let full = s.group_size.max.is_some_and(|max| group.members.len() >= max);
+    if email.is_empty() || member || full {
+        return Json(None);
+    }
+
+    if let Some(existing) = s.invitations.iter().find(|i| i.group == group.name && i.email == email) {
+        return Json(Some(existing.clone()));
+    }
+    let invitation = Invitation {
+        token: Uuid::new_v4().to_string(),
+        group: group.name.clone(),
+        email,
+    };
+    s.invitations.push(invitation.clone());
+    let _ = s.save();
+    Json(Some(invitation))
+}
+
+pub async fn student_invitations(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Vec<Invitation>> {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+    let s = state.lock().unwrap();
+
+    let Some(idx) = session_student(&s, &session) else {
+        return Json(vec![]);
+    };
+    let email = &s.students[idx].email;
+    Json(s.invitations.iter().filter(|i| &i.email == email).cloned().collect())
+}
+
+// Przyjęcie zaproszenia; student może należeć tylko do jednej grupy
+pub async fn student_accept_invitation(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<InvitationResponse>
+) -> Json<Option<Group>> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_student(&s, &body.session_id) else {
+        return Json(None);
+    };
+    let student = s.students[idx].clone();
+    let Some(invitation) = s.invitations.iter()
+        .find(|i| i.token == body.token && i.email == student.email)
+        .cloned()
+    else {
+        return Json(None);
+    };
+    let Some(g_idx) = s.groups.iter().position(|g| g.name == invitation.group) else {
+        return Json(None);
+    };
+    let full = s.group_size.max.is_some_and(|max| s.groups[g_idx].members.len() >= max);
+    if full || group_of_student(&s, &student.name).is_some() {
+        return Json(None);
+    }
+
+    s.groups[g_idx].members.push(student.name);
+    s.invitations.retain(|i| i.token != invitation.token);
+    let group = s.groups[g_idx].clone();
+    let _ = s.save();
+    Json(Some(group))
+}
+
+pub async fn student_decline_invitation(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<InvitationResponse>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_student(&s, &body.session_id) else {
+        return Json(false);
+    };
+    let email = s.students[idx].email.clone();
+    let before = s.invitations.len();
+    s.invitations.retain(|i| !(i.token == body.token && i.email == email));
+    if s.invitations.len() == before {
+        return Json(false);
+    }
+    let _ = s.save();
+    Json(true)
+}
+
+// Odejście z grupy: ranking członka znika, a lider przechodzi na pierwszego z pozostałych.
+// Grupa bez członków jest usuwana.
+pub async fn student_leave_group(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SessionRequest>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_student(&s, &body.session_id) else {
+        return Json(false);
+    };
+    let student = s.students[idx].clone();
+    let Some(g_idx) = group_of_student(&s, &student.name) else {
+        return Json(false);
+    };
+
+    let next = s.groups[g_idx].members.iter()
+        .find(|m| **m != student.name)
+        .cloned();
+    let g = &mut s.groups[g_idx];
+    g.members.retain(|m| *m != student.name);
+    g.member_rankings.retain(|r| r.member != student.name);
+    apply(g);
+    if g.leader.as_ref() == Some(&student.name) {
+        g.leader = next;
+    }
+    // Starsze grupy logowały się danymi lidera; po jego odejściu to konto nie może zostać
+    if g.email == student.email {
+        g.email.clear();
+        g.password.clear();
+    }
+
+    if g.members.is_empty() {
+        let name = g.name.clone();
+        s.remove_group(&name);
+    }
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn admin_set_application_phase(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(req): Json<SetApplicationPhase>,
+) -> Json<bool> {
+    if !is_admin(req.admin_key.as_deref()) {
+        return Json(false);
+    }
+
+    let mut s = state.lock().unwrap();
+    s.applications_required = req.required;
+    let _ = s.save();
+    Json(true)
+}
+
+const MAX_MOTIVATION: usize = 1000;
+
+// Zgłoszenie do firmy albo jej projektu; oczekujące zgłoszenie można poprawić
+pub async fn group_apply(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<Apply>
+) -> Json<Option<Application>> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_group(&s, &body.session_id) else {
+        return Json(None);
+    };
+    let group = s.groups[idx].name.clone();
+    let motivation = body.motivation.trim().to_string();
+    let known_project = body.project.as_ref()
+        .is_none_or(|id| s.projects.iter().any(|p| &p.id == id && p.company == body.company));
+    let known_company = s.companies.iter().any(|c| c.name == body.company);
+    if !known_company || !known_project || motivation.chars().count() > MAX_MOTIVATION {
+        return Json(None);
+    }
+
+    let existing = s.applications.iter_mut()
+        .find(|a| a.group == group && a.company == body.company && a.project == body.project);
+    let application = match existing {
+        Some(a) if a.status != ApplicationStatus::Pending => return Json(None),
+        Some(a) => {
+            a.motivation = motivation;
+            a.clone()
+        }
+        None => {
+            let application = Application {
+                group,
+                company: body.company,
+                project: body.project,
+                motivation,
+                status: ApplicationStatus::Pending,
+            };
+            s.applications.push(application.clone());
+            application
+        }
+    };
+    let _ = s.save();
+    Json(Some(application))
+}
+
+pub async fn group_withdraw_application(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<WithdrawApplication>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_group(&s, &body.session_id) else {
+        return Json(false);
+    };
+    let group = s.groups[idx].name.clone();
+    let before = s.applications.len();
+    s.applications.retain(|a| !(a.group == group && a.company == body.company && a.project == body.project));
+    if s.applications.len() == before {
+        return Json(false);
+    }
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn group_applications(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Vec<Application>> {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+    let s = state.lock().unwrap();
+
+    let Some(idx) = session_group(&s, &session) else {
+        return Json(vec![]);
+    };
+    let group = &s.groups[idx].name;
+    Json(s.applications.iter().filter(|a| &a.group == group).cloned().collect())
+}
+
+pub async fn company_applications(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Vec<Application>> {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+    let s = state.lock().unwrap();
+
+    let Some(email) = s.sessions.get(&session) else {
+        return Json(vec![]);
+    };
+    let Some(company) = s.companies.iter().find(|c| &c.email == email) else {
+        return Json(vec![]);
+    };
+    Json(s.applications.iter().filter(|a| a.company == company.name).cloned().collect())
+}
+
+// Firma kwalifikuje albo odrzuca zgłoszenie (albo przywraca je do oczekujących)
+pub async fn company_review_application(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<ReviewApplication>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&body.session_id) {
+        Some(e) => e.clone(),
+        None => return Json(false),
+    };
+    let Some(company) = s.companies.iter().find(|c| c.email == email).map(|c| c.name.clone()) else {
+        return Json(false);
+    };
+
+    let Some(application) = s.applications.iter_mut()
+        .find(|a| a.company == company && a.group == body.group && a.project == body.project)
+    else {
+        return Json(false);
+    };
+    application.status = body.status;
+    let _ = s.save();
+    Json(true)
+}
+
+// Przedziały w postaci "RRRR-MM-DDTGG:MM"; None, gdy któryś jest błędny
+fn normalize_ranges(ranges: Vec<TimeRange>) -> Option<Vec<TimeRange>> {
+    ranges.iter()
+        .map(|t| time_range(&t.start, &t.end).map(|(start, end)| TimeRange {
+            start: format_time(start),
+            end: format_time(end),
+        }))
+        .collect()
+}
+
+pub async fn student_set_availability(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SetAvailability>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_student(&s, &body.session_id) else {
+        return Json(false);
+    };
+    let Some(unavailable) = normalize_ranges(body.unavailable) else {
+        return Json(false);
+    };
+    s.students[idx].unavailable = unavailable;
+    let _ = s.save();
+    Json(true)
+}
+
+fn session_company(s: &AppState, session: &str) -> Option<String> {
+    let email = s.sessions.get(session)?;
+    s.companies.iter().find(|c| &c.email == email).map(|c| c.name.clone())
+}
+
+// Nowy termin rozmowy; terminy jednej firmy nie mogą na siebie nachodzić
+pub async fn company_add_slot(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<AddSlot>
+) -> Json<Option<InterviewSlot>> {
+    let mut s = state.lock().unwrap();
+
+    let Some(company) = session_company(&s, &body.session_id) else {
+        return Json(None);
+    };
+    let Some(range) = time_range(&body.start, &body.end) else {
+        return Json(None);
+    };
+    if company_busy(&s.slots, &company, range) {
+        return Json(None);
+    }
+
+    let slot = InterviewSlot {
+        id: Uuid::new_v4().to_string(),
+        company,
+        start: format_time(range.0),
+        end: format_time(range.1),
+        booked_by: None,
+    };
+    s.slots.push(slot.clone());
+    let _ = s.save();
+    Json(Some(slot))
+}
+
+// Usunięcie terminu (także zarezerwowanego, wtedy grupa traci rezerwację)
+pub async fn company_remove_slot(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SlotRequest>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let Some(company) = session_company(&s, &body.session_id) else {
+        return Json(false);
+    };
+    let before = s.slots.len();
+    s.slots.retain(|slot| !(slot.id == body.slot_id && slot.company == company));
+    if s.slots.len() == before {
+        return Json(false);
+    }
+    let _ = s.save();
+    Json(true)
+}
+
+fn sorted_slots<'a>(slots: impl Iterator<Item = &'a InterviewSlot>) -> Vec<InterviewSlot> {
+    let mut slots: Vec<InterviewSlot> = slots.cloned().collect();
+    slots.sort_by_key(|slot| parse_time(&slot.start));
+    slots
+}
+
+fn company_slots(s: &AppState, session: &str) -> Vec<InterviewSlot> {
+    match session_company(s, session) {
+        Some(company) => sorted_slots(s.slots.iter().filter(|slot| slot.company == company)),
+        None => vec![],
+    }
+}
+
+fn group_slots(s: &AppState, session: &str) -> Vec<InterviewSlot> {
+    match session_group(s, session) {
+        Some(idx) => sorted_slots(s.slots.iter().filter(|slot| slot.booked_by.as_ref() == Some(&s.groups[idx].name))),
+        None => vec![],
+    }
+}
+
+fn calendar(slots: Vec<InterviewSlot>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/calendar; charset=utf-8")], ics(&slots, now()))
+}
+
+pub async fn company_schedule(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Vec<InterviewSlot>> {
+    let session = params.get("session_id").cloned().unwrap_or_default();
     let s = state.lock().unwrap();
-    Json(stable_matching(&s.groups, &s.companies))
+    Json(company_slots(&s, &session))
+}
+
+pub async fn company_schedule_ics(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+    let s = state.lock().unwrap();
+    calendar(company_slots(&s, &session))
+}
+
+pub async fn group_schedule(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Vec<InterviewSlot>> {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+    let s = state.lock().unwrap();
+    Json(group_slots(&s, &session))
+}
+
+pub async fn group_schedule_ics(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+    let s = state.lock().unwrap();
+    calendar(group_slots(&s, &session))
+}
+
+// Wolne terminy (wszystkich firm albo jednej)
+pub async fn list_open_slots(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<SlotParams>,
+) -> Json<Vec<InterviewSlot>> {
+    let s = state.lock().unwrap();
+    Json(sorted_slots(s.slots.iter().filter(|slot| {
+        slot.booked_by.is_none() && params.company.as_ref().is_none_or(|c| *c == slot.company)
+    })))
+}
+
+pub async fn group_book_slot(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SlotRequest>
+) -> Json<BookingResponse> {
+    let mut s = state.lock().unwrap();
+    let rejected = |message: &str| Json(BookingResponse { ok: false, message: message.into(), slot: None });
+
+    let Some(idx) = session_group(&s, &body.session_id) else {
+        return rejected("Invalid session");
+    };
+    let Some(slot_idx) = s.slots.iter().position(|slot| slot.id == body.slot_id) else {
+        return rejected("Slot not found");
+    };
+    if let Some(reason) = booking_conflict(&s.slots, &s.slots[slot_idx], &s.groups[idx], &s.students) {
+        return rejected(&reason);
+    }
+
+    s.slots[slot_idx].booked_by = Some(s.groups[idx].name.clone());
+    let slot = s.slots[slot_idx].clone();
+    let _ = s.save();
+    Json(BookingResponse { ok: true, message: "Interview booked".into(), slot: Some(slot) })
+}
+
+pub async fn group_cancel_slot(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SlotRequest>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_group(&s, &body.session_id) else {
+        return Json(false);
+    };
+    let group = s.groups[idx].name.clone();
+    let Some(slot) = s.slots.iter_mut().find(|slot| slot.id == body.slot_id && slot.booked_by.as_ref() == Some(&group)) else {
+        return Json(false);
+    };
+    slot.booked_by = None;
+    let _ = s.save();
+    Json(true)
 }
 
 pub async fn login_group(
@@ -81,11 +1168,14 @@ pub async fn login_group(
 ) -> Json<LoginResponse> {
     let mut s = state.lock().unwrap();
 
-    let found = s.groups.iter().find(|g|
-        g.email == login.email && g.password == login.password
+    // Grupa bez własnego konta (założona przez studentów) loguje się danymi jednego z członków
+    let found = s.groups.iter().any(|g|
+        !g.email.is_empty() && g.email == login.email && g.password == login.password
+    ) || s.students.iter().any(|x|
+        x.email == login.email && x.password == login.password && group_of_student(&s, &x.name).is_some()
     );
 
-    if let Some(_) = found {
+    if found {
         let session_id = Uuid::new_v4().to_string();
         s.sessions.insert(session_id.clone(), login.email.clone());
         let _ = s.save();
@@ -118,7 +1208,7 @@ pub async fn login_company(
         c.email == login.email && c.password == login.password
     );
 
-    if let Some(_) = found {
+    if found.is_some() {
         let session_id = Uuid::new_v4().to_string();
         s.sessions.insert(session_id.clone(), login.email.clone());
         let _ = s.save();
@@ -149,12 +1239,7 @@ pub async fn group_me(
 
     let s = state.lock().unwrap();
 
-    let email = match s.sessions.get(&session) {
-        Some(e) => e.clone(),
-        None => return Json(None),
-    };
-
-    let g = s.groups.iter().find(|g| g.email == email).cloned();
+    let g = session_group(&s, &session).map(|idx| s.groups[idx].clone());
     Json(g)
 }
 
@@ -189,34 +1274,200 @@ pub async fn list_groups(
     Json(s.groups.clone())
 }
 
+// Dopisuje preferencję na koniec listy, opcjonalnie w remisie z ostatnią pozycją
+fn push_preference(preferences: &mut Vec<String>, tiers: &mut Vec<usize>, pref: String, tie_with_previous: bool) {
+    if preferences.contains(&pref) {
+        return;
+    }
+
+    if tiers.len() != preferences.len() {
+        tiers.clear();
+    }
+    if tie_with_previous && !preferences.is_empty() {
+        if tiers.is_empty() {
+            *tiers = (0..preferences.len()).collect();
+        }
+        tiers.push(*tiers.last().unwrap());
+    } else if let Some(&last) = tiers.last() {
+        tiers.push(last + 1);
+    }
+    preferences.push(pref);
+}
+
+// Zmiana ręcznej listy firmy; gdy lista wynika z ocen, zmienia się przechowana lista ręczna,
+// a wynikowa jest przeliczana
+fn edit_manual_list(c: &mut Company, edit: impl FnOnce(&mut Vec<String>, &mut Vec<usize>)) {
+    match &mut c.manual {
+        Some(manual) => edit(&mut manual.preferences, &mut manual.tiers),
+        None => edit(&mut c.preferences, &mut c.tiers),
+    }
+    derive_preferences(c);
+}
+
+// Poziomy -> płaska lista z poziomem każdej pozycji; puste poziomy i duplikaty są pomijane
+fn flatten_tiers(tiers: Vec<Vec<String>>) -> (Vec<String>, Vec<usize>) {
+    let mut preferences: Vec<String> = Vec::new();
+    let mut levels = Vec::new();
+
+    for tier in tiers {
+        let level = levels.last().map_or(0, |l| l + 1);
+        for pref in tier {
+            let pref = pref.trim().to_string();
+            if !pref.is_empty() && !preferences.contains(&pref) {
+                preferences.push(pref);
+                levels.push(level);
+            }
+        }
+    }
+
+    // Same jednoelementowe poziomy to zwykła ścisła lista
+    if levels.len() == levels.last().map_or(0, |l| l + 1) {
+        levels.clear();
+    }
+    (preferences, levels)
+}
+
 pub async fn group_add_pref(
     State(state): State<Arc<Mutex<AppState>>>,
     Json(body): Json<AddPref>
 ) -> Json<bool> {
     let mut s = state.lock().unwrap();
 
+    let Some(idx) = session_group(&s, &body.session_id) else {
+        return Json(false);
+    };
+    // Przy rankingach członków lista grupy jest ich agregatem; ręczna zmiana zostałaby nadpisana
+    if !size_allowed(&s.group_size, &s.groups[idx]) || !s.groups[idx].member_rankings.is_empty() {
+        return Json(false);
+    }
+
+    let g = &mut s.groups[idx];
+    if !g.preferences.contains(&body.pref) {
+        push_preference(&mut g.preferences, &mut g.tiers, body.pref, body.tie_with_previous);
+
+        let _ = s.save();
+    }
+    Json(true)
+}
+
+// Ranking jednego członka grupy; lista grupy jest od razu przeliczana
+pub async fn group_submit_ranking(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SubmitRanking>
+) -> Json<Option<AggregationReport>> {
+    let mut s = state.lock().unwrap();
+
+    // Ranking składa członek grupy swoim kontem studenta, nie wspólne konto grupy
+    let Some(member) = session_student(&s, &body.session_id).map(|i| s.students[i].name.clone()) else {
+        return Json(None);
+    };
+    let Some(idx) = group_of_student(&s, &member) else {
+        return Json(None);
+    };
+    if !size_allowed(&s.group_size, &s.groups[idx]) {
+        return Json(None);
+    }
+
+    let (preferences, _) = flatten_tiers(body.preferences.into_iter().map(|p| vec![p]).collect());
+    let known = |pref: &String| s.companies.iter().any(|c| &c.name == pref) || s.projects.iter().any(|p| &p.id == pref);
+    if !preferences.iter().all(known) {
+        return Json(None);
+    }
+
+    let g = &mut s.groups[idx];
+    let mut group = g.clone();
+    group.member_rankings.retain(|r| r.member != member);
+    if !preferences.is_empty() {
+        group.member_rankings.push(MemberRanking { member, preferences });
+    }
+    if !apply(&mut group) {
+        return Json(None);
+    }
+
+    *g = group;
+    let report = aggregation_report(g);
+    let _ = s.save();
+    Json(Some(report))
+}
+
+pub async fn group_set_aggregation(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SetAggregation>
+) -> Json<Option<AggregationReport>> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_group(&s, &body.session_id) else {
+        return Json(None);
+    };
+    if !size_allowed(&s.group_size, &s.groups[idx]) {
+        return Json(None);
+    }
+
+    let g = &mut s.groups[idx];
+    let mut group = g.clone();
+    group.aggregation = body.rule;
+    if !apply(&mut group) {
+        return Json(None);
+    }
+
+    *g = group;
+    let report = aggregation_report(g);
+    let _ = s.save();
+    Json(Some(report))
+}
+
+pub async fn group_aggregation(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Option<AggregationReport>> {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+    let s = state.lock().unwrap();
+
+    Json(session_group(&s, &session).map(|idx| aggregation_report(&s.groups[idx])))
+}
+
+pub async fn company_add_pref(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<AddPref>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
     let email = match s.sessions.get(&body.session_id) {
         Some(e) => e.clone(),
         None => return Json(false),
     };
 
-    if let Some(g) = s.groups.iter_mut().find(|x| x.email == email) {
-        if !g.preferences.contains(&body.pref) {
-            g.preferences.push(body.pref.clone());
-            
-            let _ = s.save();
-            
-            return Json(true);
-        }
+    if let Some(c) = s.companies.iter_mut().find(|x| x.email == email) {
+        edit_manual_list(c, |preferences, tiers| push_preference(preferences, tiers, body.pref, body.tie_with_previous));
+        let _ = s.save();
         return Json(true);
     }
 
     Json(false)
 }
 
-pub async fn company_add_pref(
+pub async fn group_set_prefs(
     State(state): State<Arc<Mutex<AppState>>>,
-    Json(body): Json<AddPref>
+    Json(body): Json<SetPrefs>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let Some(idx) = session_group(&s, &body.session_id) else {
+        return Json(false);
+    };
+    if !size_allowed(&s.group_size, &s.groups[idx]) || !s.groups[idx].member_rankings.is_empty() {
+        return Json(false);
+    }
+
+    let g = &mut s.groups[idx];
+    (g.preferences, g.tiers) = flatten_tiers(body.tiers);
+    let _ = s.save();
+    Json(true)
+}
+
+pub async fn company_set_prefs(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SetPrefs>
 ) -> Json<bool> {
     let mut s = state.lock().unwrap();
 
@@ -226,14 +1477,264 @@ pub async fn company_add_pref(
     };
 
     if let Some(c) = s.companies.iter_mut().find(|x| x.email == email) {
-        if !c.preferences.contains(&body.pref) {
-            c.preferences.push(body.pref.clone());
-            let _ = s.save();
-            
-            return Json(true);
-        }
+        edit_manual_list(c, |preferences, tiers| (*preferences, *tiers) = flatten_tiers(body.tiers));
+        let _ = s.save();
+        return Json(true);
+    }
+
+    Json(false)
+}
+
+pub async fn company_add_project(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<AddProject>
+) -> Json<Option<Project>> {
+    let mut s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&body.session_id) {
+        Some(e) => e.clone(),
+        None => return Json(None),
+    };
+
+    let company = match s.companies.iter().find(|c| c.email == email) {
+        Some(c) => c.name.clone(),
+        None => return Json(None),
+    };
+
+    if body.title.trim().is_empty() || body.capacity == 0 || body.min_capacity > body.capacity {
+        return Json(None);
+    }
+
+    let project = Project {
+        id: Uuid::new_v4().to_string(),
+        company,
+        title: body.title,
+        description: body.description,
+        capacity: body.capacity,
+        min_capacity: body.min_capacity,
+        required_skills: body.required_skills,
+    };
+
+    s.projects.push(project.clone());
+    let _ = s.save();
+
+    Json(Some(project))
+}
+
+// Ustawia liczbę miejsc zarezerwowanych dla kierunku (0 usuwa rezerwację)
+pub async fn company_reserve_seats(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<ReserveSeats>
+) -> Json<Option<Vec<TrackReservation>>> {
+    let mut s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&body.session_id) {
+        Some(e) => e.clone(),
+        None => return Json(None),
+    };
+
+    let track = body.track.trim().to_string();
+    if track.is_empty() {
+        return Json(None);
+    }
+
+    let company = match s.companies.iter_mut().find(|c| c.email == email) {
+        Some(c) => c,
+        None => return Json(None),
+    };
+
+    company.reserved.retain(|r| r.track != track);
+    if body.seats > 0 {
+        company.reserved.push(TrackReservation { track, seats: body.seats });
+    }
+    let reserved = company.reserved.clone();
+    let _ = s.save();
+
+    Json(Some(reserved))
+}
+
+pub async fn group_set_skills(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SetSkills>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    if let Some(idx) = session_group(&s, &body.session_id) {
+        let g = &mut s.groups[idx];
+        g.skills = clean_skills(body.skills);
+        let _ = s.save();
         return Json(true);
     }
 
     Json(false)
-}
\ No newline at end of file
+}
+
+// Kryteria oceny firmy; oceny usuniętych kryteriów przestają się liczyć
+pub async fn company_set_rubric(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SetRubric>
+) -> Json<Option<RubricReport>> {
+    let mut s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&body.session_id) {
+        Some(e) => e.clone(),
+        None => return Json(None),
+    };
+
+    let mut criteria: Vec<Criterion> = Vec::new();
+    for criterion in body.criteria {
+        let name = criterion.name.trim().to_string();
+        let valid = criterion.weight > 0.0 && criterion.max > 0.0;
+        if name.is_empty() || !valid || criteria.iter().any(|c| c.name == name) {
+            return Json(None);
+        }
+        criteria.push(Criterion { name, ..criterion });
+    }
+
+    let Some(c) = s.companies.iter_mut().find(|x| x.email == email) else {
+        return Json(None);
+    };
+    c.rubric = criteria;
+    derive_preferences(c);
+    let report = rubric_report(c);
+    let _ = s.save();
+    Json(Some(report))
+}
+
+// Oceny jednego recenzenta dla jednej grupy; lista firmy jest od razu przeliczana
+pub async fn company_submit_review(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SubmitReview>
+) -> Json<Option<RubricReport>> {
+    let mut s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&body.session_id) {
+        Some(e) => e.clone(),
+        None => return Json(None),
+    };
+
+    let reviewer = body.reviewer.trim().to_string();
+    let known_group = s.groups.iter().any(|g| g.name == body.group);
+    let Some(c) = s.companies.iter_mut().find(|x| x.email == email) else {
+        return Json(None);
+    };
+    let valid = body.scores.iter().all(|(name, score)| {
+        c.rubric.iter().any(|criterion| &criterion.name == name && (0.0..=criterion.max).contains(score))
+    });
+    if reviewer.is_empty() || !known_group || !valid {
+        return Json(None);
+    }
+
+    c.reviews.retain(|r| !(r.reviewer == reviewer && r.group == body.group));
+    if !body.scores.is_empty() {
+        c.reviews.push(Review { reviewer, group: body.group, scores: body.scores });
+    }
+    derive_preferences(c);
+    let report = rubric_report(c);
+    let _ = s.save();
+    Json(Some(report))
+}
+
+pub async fn company_rubric(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Option<RubricReport>> {
+    let session = params.get("session_id").cloned().unwrap_or_default();
+    let s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&session) {
+        Some(e) => e.clone(),
+        None => return Json(None),
+    };
+
+    Json(s.companies.iter().find(|c| c.email == email).map(rubric_report))
+}
+
+// Wymagania całej firmy albo (z `project`) jednego z jej projektów
+pub async fn company_set_skills(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Json(body): Json<SetSkills>
+) -> Json<bool> {
+    let mut s = state.lock().unwrap();
+
+    let email = match s.sessions.get(&body.session_id) {
+        Some(e) => e.clone(),
+        None => return Json(false),
+    };
+
+    let company = match s.companies.iter().find(|c| c.email == email) {
+        Some(c) => c.name.clone(),
+        None => return Json(false),
+    };
+
+    let skills = clean_skills(body.skills);
+    match &body.project {
+        Some(id) => match s.projects.iter_mut().find(|p| &p.id == id && p.company == company) {
+            Some(project) => project.required_skills = skills,
+            None => return Json(false),
+        },
+        None => {
+            if let Some(c) = s.companies.iter_mut().find(|c| c.name == company) {
+                c.required_skills = skills;
+            }
+        }
+    }
+    let _ = s.save();
+    Json(true)
+}
+
+fn clean_skills(skills: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for skill in skills {
+        let skill = skill.trim().to_string();
+        if !skill.is_empty() && !cleaned.iter().any(|s| s.eq_ignore_ascii_case(&skill)) {
+            cleaned.push(skill);
+        }
+    }
+    cleaned
+}
+
+pub async fn company_eligibility(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<EligibilityParams>,
+) -> Json<Option<EligibilityReport>> {
+    let s = state.lock().unwrap();
+    let Some(company) = s.companies.iter().find(|c| c.name == params.company) else {
+        return Json(None);
+    };
+    Json(Some(eligibility_report(&s.groups, company, &s.projects)))
+}
+
+pub async fn list_projects(
+    State(state): State<Arc<Mutex<AppState>>>
+) -> Json<Vec<Project>> {
+    let s = state.lock().unwrap();
+    Json(company_projects(&s.companies, &s.projects))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(s: AppState) -> State<Arc<Mutex<AppState>>> {
+        State(Arc::new(Mutex::new(s)))
+    }
+
+    #[tokio::test]
+    async fn student_list_hides_passwords() {
+        let mut s = AppState::new();
+        s.students.push(Student {
+            name: "ala".into(),
+            email: "ala@example.com".into(),
+            password: "secret".into(),
+            skills: vec!["rust".into()],
+            want_with: vec![],
+            unavailable: vec![],
+        });
+
+        let Json(list) = list_students(state(s)).await;
+        let body = serde_json::to_string(&list).unwrap();
+        assert!(body.contains("ala@example.com") && body.contains("rust"));
+        assert!(!body.contains("password") && !body.contains("secret"), "{}", body);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::models::{Company, Review, CriterionAverage, GroupScore, RubricReport, RankedList};
+
+// Wyniki różniące się mniej niż o tyle są remisem
+const TIE_EPSILON: f64 = 1e-9;
+
+// Grupy w kolejności pierwszej oceny
+fn reviewed_groups(reviews: &[Review]) -> Vec<String> {
+    let mut groups: Vec<String> = Vec::new();
+    for review in reviews {
+        if !groups.contains(&review.group) {
+            groups.push(review.group.clone());
+        }
+    }
+    groups
+}
+
+// Średnie ocen grupy w każdym kryterium i ich ważona suma po normalizacji do [0, 1];
+// kryterium bez żadnej oceny liczy się jako 0
+fn group_score(company: &Company, group: &str) -> GroupScore {
+    let total_weight: f64 = company.rubric.iter().map(|c| c.weight).sum();
+    let mut total = 0.0;
+    let criteria = company.rubric.iter()
+        .map(|criterion| {
+            let values: Vec<f64> = company.reviews.iter()
+                .filter(|r| r.group == group)
+                .filter_map(|r| r.scores.get(&criterion.name).copied())
+                .collect();
+            let average = (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64);
+            if let Some(average) = average {
+                total += criterion.weight * (average / criterion.max).clamp(0.0, 1.0);
+            }
+            CriterionAverage {
+                criterion: criterion.name.clone(),
+                average,
+                reviewers: values.len(),
+            }
+        })
+        .collect();
+
+    GroupScore {
+        group: group.to_string(),
+        total: if total_weight > 0.0 { total / total_weight } else { 0.0 },
+        criteria,
+    }
+}
+
+// Ocenione grupy od najwyższego wyniku
+pub fn group_scores(company: &Company) -> Vec<GroupScore> {
+    let mut scores: Vec<GroupScore> = reviewed_groups(&company.reviews).iter()
+        .map(|g| group_score(company, g))
+        .collect();
+    scores.sort_by(|a, b| b.total.total_cmp(&a.total));
+    scores
+}
+
+// Lista firmy z wyników rubryki (równe wyniki = ten sam poziom), a za nią nieocenione grupy z listy
+// ręcznej w jej kolejności. Lista ręczna czeka w `manual` i wraca, gdy nie ma już ocen.
+pub fn derive_preferences(company: &mut Company) {
+    if company.rubric.is_empty() || company.reviews.is_empty() {
+        if let Some(manual) = company.manual.take() {
+            (company.preferences, company.tiers) = (manual.preferences, manual.tiers);
+        }
+        return;
+    }
+    let manual = company.manual
+        .get_or_insert_with(|| RankedList { preferences: company.preferences.clone(), tiers: company.tiers.clone() })
+        .clone();
+    let scores = group_scores(company);
+
+    let mut tiers: Vec<usize> = Vec::new();
+    for (i, score) in scores.iter().enumerate() {
+        let tie = i > 0 && (scores[i - 1].total - score.total).abs() < TIE_EPSILON;
+        tiers.push(if tie { tiers[i - 1] } else { i });
+    }
+    let mut preferences: Vec<String> = scores.into_iter().map(|s| s.group).collect();
+
+    // Poziomy listy ręcznej przesunięte za ocenione grupy, z zachowaniem ręcznych remisów
+    let offset = preferences.len();
+    let strict = manual.tiers.len() != manual.preferences.len();
+    for (i, group) in manual.preferences.iter().enumerate() {
+        if !preferences.contains(group) {
+            preferences.push(group.clone());
+            tiers.push(offset + if strict { i } else { manual.tiers[i] });
+        }
+    }
+
+    // Bez remisów zwykła ścisła lista
+    if tiers.windows(2).all(|w| w[0] != w[1]) {
+        tiers.clear();
+    }
+    company.preferences = preferences;
+    company.tiers = tiers;
+}
+
+pub fn rubric_report(company: &Company) -> RubricReport {
+    RubricReport {
+        company: company.name.clone(),
+        criteria: company.rubric.clone(),
+        scores: group_scores(company),
+        preferences: company.preferences.clone(),
+        tiers: company.tiers.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Criterion;
+    use std::collections::BTreeMap;
+
+    fn review(reviewer: &str, group: &str, scores: &[(&str, f64)]) -> Review {
+        Review {
+            reviewer: reviewer.into(),
+            group: group.into(),
+            scores: scores.iter().map(|(c, s)| (c.to_string(), *s)).collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn weighted_averages_rank_groups_with_ties() {
+        let criterion = |name: &str, weight: f64, max: f64| Criterion { name: name.into(), weight, max };
+        let mut company = Company {
+            name: "c".into(),
+            email: "c".into(),
+            preferences: vec!["manual".into()],
+            rubric: vec![criterion("experience", 2.0, 10.0), criterion("motivation", 1.0, 5.0)],
+            ..Default::default()
+        };
+
+        // Bez ocen zostaje lista ręczna
+        derive_preferences(&mut company);
+        assert_eq!(company.preferences, ["manual"]);
+        assert!(company.manual.is_none());
+
+        company.reviews = vec![
+            // a: doświadczenie (8 + 4) / 2 = 6 -> 0.6, motywacja 5 -> 1.0; (2 * 0.6 + 1.0) / 3
+            review("ala", "a", &[("experience", 8.0), ("motivation", 5.0)]),
+            review("ola", "a", &[("experience", 4.0)]),
+            // b: 1.0 i 0.2 -> (2 + 0.2) / 3, tyle samo co a
+            review("ala", "b", &[("experience", 10.0), ("motivation", 1.0)]),
+            // c: tylko motywacja 5 -> 1 / 3
+            review("ola", "c", &[("motivation", 5.0)]),
+            // d: ocena ponad skalę liczy się jak maksimum
+            review("ala", "d", &[("experience", 20.0), ("motivation", 5.0)]),
+        ];
+        derive_preferences(&mut company);
+
+        let scores = group_scores(&company);
+        assert!((scores[0].total - 1.0).abs() < 1e-9);
+        assert!((scores[1].total - 2.2 / 3.0).abs() < 1e-9);
+        assert_eq!(scores[1].criteria[0].reviewers, 2);
+        assert_eq!(company.preferences, ["d", "a", "b", "c", "manual"]);
+        assert_eq!(company.tiers, [0, 1, 1, 3, 4]);
+
+        // Wszystkie wyniki różne: ścisła lista
+        company.reviews.retain(|r| r.group != "b");
+        derive_preferences(&mut company);
+        assert_eq!(company.preferences, ["d", "a", "c", "manual"]);
+        assert!(company.tiers.is_empty());
+
+        // Bez ocen wraca lista ręczna
+        company.reviews.clear();
+        derive_preferences(&mut company);
+        assert_eq!(company.preferences, ["manual"]);
+        assert!(company.manual.is_none());
+    }
+
+    #[test]
+    fn unreviewed_groups_follow_in_manual_order() {
+        let mut company = Company {
+            name: "c".into(),
+            preferences: vec!["x".into(), "a".into(), "y".into(), "z".into()],
+            tiers: vec![0, 1, 2, 2],
+            rubric: vec![Criterion { name: "fit".into(), weight: 1.0, max: 10.0 }],
+            reviews: vec![review("ala", "a", &[("fit", 7.0)]), review("ala", "b", &[("fit", 9.0)])],
+            ..Default::default()
+        };
+        derive_preferences(&mut company);
+        assert_eq!(company.preferences, ["b", "a", "x", "y", "z"]);
+        assert_eq!(company.tiers, [0, 1, 2, 4, 4]);
+        assert_eq!(company.manual.as_ref().unwrap().preferences, ["x", "a", "y", "z"]);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::models::{Group, Student, InterviewSlot};
+
+// Dni od 1970-01-01 dla daty kalendarza gregoriańskiego (algorytm H. Hinnanta)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+// "RRRR-MM-DDTGG:MM" (albo ze spacją zamiast T) jako minuty od 1970-01-01T00:00 tego samego zegara;
+// czasy są lokalne, więc porównuje się je tylko ze sobą
+pub fn parse_time(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let bytes = text.as_bytes();
+    if bytes.len() != 16 || bytes[4] != b'-' || bytes[7] != b'-' || !matches!(bytes[10], b'T' | b' ') || bytes[13] != b':' {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let part = &text[range];
+        part.bytes().all(|b| b.is_ascii_digit()).then(|| part.parse().ok())?
+    };
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute) = (number(11..13)?, number(14..16)?);
+    if hour >= 24 || minute >= 60 || !(1..=12).contains(&month) {
+        return None;
+    }
+
+    // Odrzuca daty, które nie istnieją (np. 30 lutego)
+    let days = days_from_civil(year, month, day);
+    (civil_from_days(days) == (year, month, day)).then_some(days * 1440 + hour * 60 + minute)
+}
+
+pub fn format_time(minutes: i64) -> String {
+    let (year, month, day) = civil_from_days(minutes.div_euclid(1440));
+    let minute_of_day = minutes.rem_euclid(1440);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}", year, month, day, minute_of_day / 60, minute_of_day % 60)
+}
+
+// Przedział [początek, koniec) w minutach; None, gdy czas jest błędny albo koniec nie jest po początku
+pub fn time_range(start: &str, end: &str) -> Option<(i64, i64)> {
+    let range = (parse_time(start)?, parse_time(end)?);
+    (range.0 < range.1).then_some(range)
+}
+
+pub fn overlaps(a: (i64, i64), b: (i64, i64)) -> bool {
+    a.0 < b.1 && b.0 < a.1
+}
+
+fn span(slot: &InterviewSlot) -> Option<(i64, i64)> {
+    time_range(&slot.start, &slot.end)
+}
+
+// Czy nowy termin firmy nachodzi na któryś z jej terminów
+pub fn company_busy(slots: &[InterviewSlot], company: &str, range: (i64, i64)) -> bool {
+    slots.iter()
+        .filter(|s| s.company == company)
+        .any(|s| span(s).is_some_and(|other| overlaps(other, range)))
+}
+
+// Powód, dla którego grupa nie może zarezerwować terminu: zajęty termin, druga rozmowa z tą samą firmą,
+// inna rozmowa grupy w tym czasie albo niedostępność któregoś z członków
+pub fn booking_conflict(slots: &[InterviewSlot], slot: &InterviewSlot, group: &Group, students: &[Student]) -> Option<String> {
+    if slot.booked_by.is_some() {
+        return Some("Slot is already booked".into());
+    }
+    let Some(range) = span(slot) else {
+        return Some("Slot has an invalid time".into());
+    };
+
+    for other in slots.iter().filter(|s| s.booked_by.as_ref() == Some(&group.name)) {
+        if other.company == slot.company {
+            return Some(format!("Group already has an interview with {}", other.company));
+        }
+        if span(other).is_some_and(|o| overlaps(o, range)) {
+            return Some(format!("Group has another interview at that time ({}, {})", other.company, other.start));
+        }
+    }
+
+    for member in &group.members {
+        let busy = students.iter()
+            .find(|s| &s.name == member)
+            .is_some_and(|s| s.unavailable.iter()
+                .any(|t| time_range(&t.start, &t.end).is_some_and(|t| overlaps(t, range))));
+        if busy {
+            return Some(format!("{} is unavailable at that time", member));
+        }
+    }
+    None
+}
+
+fn ics_time(seconds: i64) -> String {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let second_of_day = seconds.rem_euclid(86400);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}",
+        year, month, day, second_of_day / 3600, second_of_day / 60 % 60, second_of_day % 60,
+    )
+}
+
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+// Kalendarz iCalendar (RFC 5545) z terminami; `stamp` to chwila eksportu w sekundach UTC.
+// Terminy są w czasie "pływającym" (bez Z i TZID), więc kalendarz pokaże tę samą godzinę, którą wpisano
+pub fn ics(slots: &[InterviewSlot], stamp: u64) -> String {
+    let mut out = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//ZPPMatch//Interviews//EN\r\nCALSCALE:GREGORIAN\r\n");
+    for slot in slots {
+        let Some((start, end)) = span(slot) else {
+            continue;
+        };
+        let summary = match &slot.booked_by {
+            Some(group) => format!("Interview: {} with {}", group, slot.company),
+            None => format!("Open interview slot ({})", slot.company),
+        };
+        out += "BEGIN:VEVENT\r\n";
+        out += &format!("UID:{}@zppmatch\r\n", slot.id);
+        out += &format!("DTSTAMP:{}Z\r\n", ics_time(stamp as i64));
+        out += &format!("DTSTART:{}\r\n", ics_time(start * 60));
+        out += &format!("DTEND:{}\r\n", ics_time(end * 60));
+        out += &format!("SUMMARY:{}\r\n", ics_text(&summary));
+        out += "END:VEVENT\r\n";
+    }
+    out += "END:VCALENDAR\r\n";
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TimeRange;
+
+    fn slot(id: &str, company: &str, start: &str, end: &str, booked_by: Option<&str>) -> InterviewSlot {
+        InterviewSlot {
+            id: id.into(),
+            company: company.into(),
+            start: start.into(),
+            end: end.into(),
+            booked_by: booked_by.map(|g| g.into()),
+        }
+    }
+
+    #[test]
+    fn times_round_trip_and_reject_invalid_dates() {
+        for text in ["1970-01-01T00:00", "2024-02-29T23:59", "2026-10-18T09:30", "2100-12-31T12:00"] {
+            assert_eq!(format_time(parse_time(text).unwrap()), text);
+        }
+        assert_eq!(parse_time("1970-01-02 01:01"), Some(1440 + 61));
+        for text in ["2025-02-29T10:00", "2026-04-31T10:00", "2026-13-01T10:00", "2026-01-01T24:00", "2026-1-01T10:00", "2026-01-01T10:0a"] {
+            assert_eq!(parse_time(text), None, "{}", text);
+        }
+        assert_eq!(time_range("2026-01-01T10:00", "2026-01-01T10:00"), None);
+    }
+
+    #[test]
+    fn bookings_respect_slots_groups_and_members() {
+        let group = Group {
+            name: "g".into(),
+            email: "g".into(),
+            preferences: vec![],
+            members: vec!["ala".into()],
+            leader: Some("ala".into()),
+            ..Default::default()
+        };
+        let mut ala = Student {
+            name: "ala".into(),
+            email: "ala".into(),
+            password: String::new(),
+            skills: vec![],
+            want_with: vec![],
+            unavailable: vec![],
+        };
+        let slots = vec![
+            slot("1", "a", "2026-03-02T10:00", "2026-03-02T10:30", Some("g")),
+            slot("2", "a", "2026-03-02T11:00", "2026-03-02T11:30", None),
+            slot("3", "b", "2026-03-02T10:15", "2026-03-02T10:45", None),
+            slot("4", "b", "2026-03-02T10:30", "2026-03-02T11:00", None),
+            slot("5", "c", "2026-03-02T12:00", "2026-03-02T12:30", Some("h")),
+        ];
+
+        let conflict = |slot: &InterviewSlot, ala: &Student| booking_conflict(&slots, slot, &group, std::slice::from_ref(ala));
+        assert!(conflict(&slots[1], &ala).unwrap().contains("already has an interview with a"));
+        assert!(conflict(&slots[2], &ala).unwrap().contains("another interview"));
+        assert_eq!(conflict(&slots[3], &ala), None);
+        assert_eq!(conflict(&slots[4], &ala).unwrap(), "Slot is already booked");
+
+        ala.unavailable.push(TimeRange { start: "2026-03-02T10:59".into(), end: "2026-03-02T12:00".into() });
+        assert_eq!(conflict(&slots[3], &ala).unwrap(), "ala is unavailable at that time");
+
+        assert!(company_busy(&slots, "a", (parse_time("2026-03-02T10:29").unwrap(), parse_time("2026-03-02T10:40").unwrap())));
+        assert!(!company_busy(&slots, "a", (parse_time("2026-03-02T10:30").unwrap(), parse_time("2026-03-02T11:00").unwrap())));
+    }
+
+    #[test]
+    fn calendar_export() {
+        let calendar = ics(&[slot("1", "a;b", "2026-03-02T10:00", "2026-03-02T10:30", Some("g"))], 86400 + 3661);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n") && calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("DTSTART:20260302T100000\r\nDTEND:20260302T103000\r\n"));
+        assert!(calendar.contains("DTSTAMP:19700102T010101Z\r\n"));
+        assert!(calendar.contains("SUMMARY:Interview: g with a\\;b\r\n"));
+    }
+}
@@ -1,12 +1,35 @@
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
-use crate::models::{Group, Company};
+use crate::rubric;
+use crate::models::{Group, Company, Project, MatchRun, Publication, PairConstraints, Student, TeamProposal, Invitation, GroupSizeLimits, Application, InterviewSlot};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct AppState {
     pub groups: Vec<Group>,
     pub companies: Vec<Company>,
+    #[serde(default)]
+    pub projects: Vec<Project>,
     pub sessions: HashMap<String, String>,
+    #[serde(default)]
+    pub runs: Vec<MatchRun>,
+    #[serde(default)]
+    pub published: Option<Publication>,
+    #[serde(default)]
+    pub constraints: PairConstraints,
+    #[serde(default)]
+    pub students: Vec<Student>,
+    #[serde(default)]
+    pub team_proposal: Option<TeamProposal>,
+    #[serde(default)]
+    pub invitations: Vec<Invitation>,
+    #[serde(default)]
+    pub group_size: GroupSizeLimits,
+    #[serde(default)]
+    pub applications: Vec<Application>,
+    #[serde(default)]
+    pub applications_required: bool,
+    #[serde(default)]
+    pub slots: Vec<InterviewSlot>,
 }
 
 impl AppState {
@@ -14,12 +37,149 @@ impl AppState {
         AppState {
             groups: Vec::new(),
             companies: Vec::new(),
+            projects: Vec::new(),
             sessions: HashMap::new(),
+            runs: Vec::new(),
+            published: None,
+            constraints: PairConstraints::default(),
+            students: Vec::new(),
+            team_proposal: None,
+            invitations: Vec::new(),
+            group_size: GroupSizeLimits::default(),
+            applications: Vec::new(),
+            applications_required: false,
+            slots: Vec::new(),
         }
     }
     
+    // Sesja pamięta tylko adres e-mail, więc jeden adres należy do jednego konta:
+    // studenta, grupy albo firmy. Pusty adres nigdy nie jest wolny
+    pub fn email_in_use(&self, email: &str) -> bool {
+        email.trim().is_empty()
+            || self.students.iter().any(|x| x.email == email)
+            || self.groups.iter().any(|g| g.email == email)
+            || self.companies.iter().any(|c| c.email == email)
+    }
+
+    // Usuwa grupę razem ze wszystkim, co się do niej odwołuje: zaproszeniami, aplikacjami,
+    // przypięciami i zakazami, rezerwacjami rozmów, ocenami i miejscem na listach firm
+    pub fn remove_group(&mut self, name: &str) {
+        self.groups.retain(|g| g.name != name);
+        self.invitations.retain(|i| i.group != name);
+        self.applications.retain(|a| a.group != name);
+        self.constraints.pinned.retain(|c| c.group != name);
+        self.constraints.forbidden.retain(|c| c.group != name);
+        for slot in &mut self.slots {
+            if slot.booked_by.as_deref() == Some(name) {
+                slot.booked_by = None;
+            }
+        }
+        for c in &mut self.companies {
+            c.reviews.retain(|r| r.group != name);
+            if let Some(manual) = &mut c.manual {
+                remove_preference(&mut manual.preferences, &mut manual.tiers, name);
+            }
+            remove_preference(&mut c.preferences, &mut c.tiers, name);
+            rubric::derive_preferences(c);
+        }
+    }
+
     pub fn save(&self) -> std::io::Result<()> {
         let json = serde_json::to_string_pretty(self)?;
         std::fs::write("state.json", json)
     }
-}
\ No newline at end of file
+}
+
+// Usuwa pozycję z listy razem z jej poziomem, jeśli lista ma poziomy
+fn remove_preference(preferences: &mut Vec<String>, tiers: &mut Vec<usize>, name: &str) {
+    if let Some(i) = preferences.iter().position(|p| p == name) {
+        preferences.remove(i);
+        if tiers.len() > i {
+            tiers.remove(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PairConstraint, Criterion, Review, RankedList};
+
+    #[test]
+    fn one_email_per_account() {
+        let mut s = AppState::new();
+        s.students.push(Student {
+            name: "ala".into(),
+            email: "ala@example.com".into(),
+            password: "a".into(),
+            skills: vec![],
+            want_with: vec![],
+            unavailable: vec![],
+        });
+        s.groups.push(Group { name: "g1".into(), email: "g1@example.com".into(), ..Default::default() });
+        s.groups.push(Group { name: "team-1".into(), members: vec!["ala".into()], ..Default::default() });
+        s.companies.push(Company { name: "c1".into(), email: "c1@example.com".into(), ..Default::default() });
+
+        for email in ["ala@example.com", "g1@example.com", "c1@example.com", "", " "] {
+            assert!(s.email_in_use(email), "{:?}", email);
+        }
+        assert!(!s.email_in_use("ola@example.com"));
+    }
+
+    #[test]
+    fn removing_a_group_clears_everything_that_names_it() {
+        let mut s = AppState::new();
+        for name in ["g1", "g2"] {
+            s.groups.push(Group { name: name.into(), members: vec![format!("{}-member", name)], ..Default::default() });
+            s.invitations.push(Invitation { token: name.into(), group: name.into(), email: "x@example.com".into() });
+            s.applications.push(Application {
+                group: name.into(),
+                company: "c1".into(),
+                project: None,
+                motivation: String::new(),
+                status: Default::default(),
+            });
+            let pair = PairConstraint { group: name.into(), company: "c1".into(), project: None };
+            s.constraints.pinned.push(pair.clone());
+            s.constraints.forbidden.push(pair);
+            s.slots.push(InterviewSlot {
+                id: name.into(),
+                company: "c1".into(),
+                start: "2026-01-01T10:00".into(),
+                end: "2026-01-01T10:30".into(),
+                booked_by: Some(name.into()),
+            });
+        }
+        s.companies.push(Company {
+            name: "c1".into(),
+            preferences: vec!["g1".into(), "g2".into(), "g3".into()],
+            tiers: vec![0, 0, 1],
+            ..Default::default()
+        });
+        s.companies.push(Company {
+            name: "c2".into(),
+            rubric: vec![Criterion { name: "code".into(), weight: 1.0, max: 5.0 }],
+            reviews: vec![
+                Review { reviewer: "r".into(), group: "g1".into(), scores: [("code".to_string(), 5.0)].into() },
+                Review { reviewer: "r".into(), group: "g2".into(), scores: [("code".to_string(), 3.0)].into() },
+            ],
+            preferences: vec!["g1".into(), "g2".into()],
+            manual: Some(RankedList { preferences: vec!["g2".into(), "g1".into(), "g3".into()], tiers: vec![] }),
+            ..Default::default()
+        });
+
+        s.remove_group("g1");
+
+        assert_eq!(s.groups.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(), ["g2"]);
+        assert!(s.invitations.iter().all(|i| i.group == "g2"));
+        assert!(s.applications.iter().all(|a| a.group == "g2"));
+        assert!(s.constraints.pinned.iter().chain(&s.constraints.forbidden).all(|c| c.group == "g2"));
+        assert_eq!(s.slots.iter().map(|x| x.booked_by.as_deref()).collect::<Vec<_>>(), [None, Some("g2")]);
+
+        assert_eq!(s.companies[0].preferences, ["g2", "g3"]);
+        assert_eq!(s.companies[0].tiers, [0, 1]);
+        // Lista z ocen przebudowana bez grupy, lista ręczna też jej już nie ma
+        assert_eq!(s.companies[1].preferences, ["g2", "g3"]);
+        assert_eq!(s.companies[1].manual.as_ref().unwrap().preferences, ["g2", "g3"]);
+    }
+}
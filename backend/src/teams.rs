@@ -0,0 +1,233 @@
+use crate::models::{Student, TeamProposal, ProposedTeam};
+use std::collections::HashSet;
+
+// Wagi celu: wzajemne życzenie waży więcej niż wszystko, co jedna zamiana może zmienić w pozostałych
+const MUTUAL: i64 = 1000;
+const ONE_SIDED: i64 = 10;
+const SKILL: i64 = 1;
+
+fn wants(students: &[Student], a: usize, b: usize) -> bool {
+    students[a].want_with.contains(&students[b].name)
+}
+
+fn normalized_skills(student: &Student) -> HashSet<String> {
+    student.skills.iter().map(|s| s.trim().to_lowercase()).collect()
+}
+
+fn team_score(students: &[Student], skills: &[HashSet<String>], team: &[usize]) -> i64 {
+    let mut score = 0;
+    for (i, &a) in team.iter().enumerate() {
+        for &b in &team[i + 1..] {
+            score += match (wants(students, a, b), wants(students, b, a)) {
+                (true, true) => MUTUAL,
+                (true, false) | (false, true) => ONE_SIDED,
+                (false, false) => 0,
+            };
+        }
+    }
+    let covered: HashSet<&String> = team.iter().flat_map(|&s| &skills[s]).collect();
+    score + SKILL * covered.len() as i64
+}
+
+// Rozmiary zespołów jak najbliższe `size` i różniące się najwyżej o jeden
+fn team_sizes(count: usize, size: usize) -> Vec<usize> {
+    let teams = count.div_ceil(size.max(1));
+    (0..teams)
+        .map(|t| count / teams + usize::from(t < count % teams))
+        .collect()
+}
+
+// Zespoły: najpierw pary wzajemnych życzeń trafiają razem, potem zamiany dwóch osób
+// z różnych zespołów, dopóki któraś poprawia łączny wynik
+pub fn form_teams(students: &[Student], size: usize) -> Vec<Vec<usize>> {
+    if students.is_empty() {
+        return vec![];
+    }
+    let skills: Vec<HashSet<String>> = students.iter().map(normalized_skills).collect();
+    let sizes = team_sizes(students.len(), size);
+    let mut teams: Vec<Vec<usize>> = vec![Vec::new(); sizes.len()];
+
+    // Pary wzajemnych życzeń jako jednostki, rozkładane przed pojedynczymi osobami
+    let mut placed = vec![false; students.len()];
+    let mut units: Vec<Vec<usize>> = Vec::new();
+    for a in 0..students.len() {
+        if placed[a] {
+            continue;
+        }
+        placed[a] = true;
+        let partner = (a + 1..students.len())
+            .find(|&b| !placed[b] && wants(students, a, b) && wants(students, b, a));
+        match partner {
+            Some(b) => {
+                placed[b] = true;
+                units.push(vec![a, b]);
+            }
+            None => units.push(vec![a]),
+        }
+    }
+    units.sort_by_key(|unit| std::cmp::Reverse(unit.len()));
+    for unit in units {
+        match (0..teams.len()).find(|&t| teams[t].len() + unit.len() <= sizes[t]) {
+            Some(t) => teams[t].extend(unit),
+            None => {
+                for s in unit {
+                    let t = (0..teams.len()).find(|&t| teams[t].len() < sizes[t]).unwrap();
+                    teams[t].push(s);
+                }
+            }
+        }
+    }
+
+    loop {
+        let mut improved = false;
+        for t in 0..teams.len() {
+            for u in t + 1..teams.len() {
+                for i in 0..teams[t].len() {
+                    for j in 0..teams[u].len() {
+                        let before = team_score(students, &skills, &teams[t]) + team_score(students, &skills, &teams[u]);
+                        let (a, b) = (teams[t][i], teams[u][j]);
+                        teams[t][i] = b;
+                        teams[u][j] = a;
+                        let after = team_score(students, &skills, &teams[t]) + team_score(students, &skills, &teams[u]);
+                        if after > before {
+                            improved = true;
+                        } else {
+                            teams[t][i] = a;
+                            teams[u][j] = b;
+                        }
+                    }
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    for team in &mut teams {
+        team.sort();
+    }
+    teams
+}
+
+pub fn propose_teams(students: &[Student], size: usize) -> TeamProposal {
+    let teams = form_teams(students, size);
+    let team_of = |s: usize| teams.iter().position(|t| t.contains(&s));
+
+    let mut split_pairs = Vec::new();
+    for a in 0..students.len() {
+        for b in a + 1..students.len() {
+            if wants(students, a, b) && wants(students, b, a) && team_of(a) != team_of(b) {
+                split_pairs.push(vec![students[a].name.clone(), students[b].name.clone()]);
+            }
+        }
+    }
+
+    TeamProposal {
+        size,
+        teams: teams.iter()
+            .map(|team| {
+                let mut skills: Vec<String> = Vec::new();
+                for &s in team {
+                    for skill in &students[s].skills {
+                        if !skills.iter().any(|k| k.eq_ignore_ascii_case(skill)) {
+                            skills.push(skill.clone());
+                        }
+                    }
+                }
+                let wishes_met = team.iter()
+                    .map(|&a| team.iter().filter(|&&b| wants(students, a, b)).count())
+                    .sum();
+                ProposedTeam {
+                    members: team.iter().map(|&s| students[s].name.clone()).collect(),
+                    skills,
+                    wishes_met,
+                }
+            })
+            .collect(),
+        split_pairs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::seq::SliceRandom;
+    use rand_chacha::ChaCha8Rng;
+
+    fn student(name: String, skills: Vec<String>, want_with: Vec<String>) -> Student {
+        Student {
+            email: format!("{}@example.com", name),
+            name,
+            password: String::new(),
+            skills,
+            want_with,
+            unavailable: vec![],
+        }
+    }
+
+    #[test]
+    fn teams_are_balanced_and_keep_mutual_pairs() {
+        for seed in 0..200 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let size = rng.gen_range(2..=4);
+            let count = rng.gen_range(1..=12);
+            let names: Vec<String> = (0..count).map(|i| format!("s{}", i)).collect();
+
+            // Rozłączne pary wzajemnych życzeń, plus życzenia jednostronne i umiejętności
+            let mut shuffled = names.clone();
+            shuffled.shuffle(&mut rng);
+            let pairs: Vec<(String, String)> = shuffled.chunks(2)
+                .filter(|c| c.len() == 2 && rng.gen_bool(0.6))
+                .map(|c| (c[0].clone(), c[1].clone()))
+                .collect();
+            let students: Vec<Student> = names.iter()
+                .map(|name| {
+                    let mut want_with: Vec<String> = pairs.iter()
+                        .filter_map(|(a, b)| if a == name { Some(b.clone()) } else if b == name { Some(a.clone()) } else { None })
+                        .collect();
+                    if rng.gen_bool(0.3) {
+                        want_with.push(names[rng.gen_range(0..count)].clone());
+                    }
+                    let skills = ["rust", "ml", "web", "db"].iter()
+                        .filter(|_| rng.gen_bool(0.4))
+                        .map(|s| s.to_string())
+                        .collect();
+                    student(name.clone(), skills, want_with)
+                })
+                .collect();
+
+            let proposal = propose_teams(&students, size);
+            let mut members: Vec<&String> = proposal.teams.iter().flat_map(|t| &t.members).collect();
+            members.sort();
+            members.dedup();
+            assert_eq!(members.len(), count, "seed {}", seed);
+
+            let smallest = proposal.teams.iter().map(|t| t.members.len()).min().unwrap();
+            let largest = proposal.teams.iter().map(|t| t.members.len()).max().unwrap();
+            assert!(largest <= size && largest - smallest <= 1, "seed {}", seed);
+
+            // Przy parzystym rozmiarze i pełnych zespołach wszystkie rozłączne pary da się zachować
+            // (losowe życzenia jednostronne mogą dołożyć nakładające się pary wzajemne)
+            if size % 2 == 0 && count % size == 0 {
+                for (a, b) in &pairs {
+                    assert!(proposal.teams.iter().any(|t| t.members.contains(a) && t.members.contains(b)), "seed {}: {} {}", seed, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn skills_are_spread_across_teams() {
+        let skilled = |name: &str, skill: &str| student(name.into(), vec![skill.into()], vec![]);
+        let students = [
+            skilled("a", "rust"),
+            skilled("b", "rust"),
+            skilled("c", "web"),
+            skilled("d", "web"),
+        ];
+        let proposal = propose_teams(&students, 2);
+        assert!(proposal.teams.iter().all(|t| t.skills.len() == 2), "{:?}", proposal.teams);
+    }
+}
@@ -12,6 +12,8 @@ pub struct Company {
     pub email: String,
     pub password: String,
     pub preferences: Vec<String>,
+    #[serde(default)]
+    pub tiers: Vec<usize>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -22,10 +24,117 @@ pub struct Group {
     pub preferences: Vec<String>,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct Project {
+    pub id: String,
+    pub company: String,
+    pub title: String,
+    pub description: String,
+    pub capacity: usize,
+}
+
+#[derive(Serialize)]
+struct AddProjectRequest {
+    session_id: String,
+    title: String,
+    description: String,
+    capacity: usize,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Application {
+    pub group: String,
+    pub project: Option<String>,
+    pub motivation: String,
+    pub status: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Criterion {
+    pub name: String,
+    pub weight: f64,
+    pub max: f64,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct GroupScore {
+    pub group: String,
+    pub total: f64,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RubricReport {
+    pub criteria: Vec<Criterion>,
+    pub scores: Vec<GroupScore>,
+}
+
+#[derive(Serialize)]
+struct SetRubricRequest {
+    session_id: String,
+    criteria: Vec<Criterion>,
+}
+
+#[derive(Serialize)]
+struct ScoreRequest {
+    session_id: String,
+    reviewer: String,
+    group: String,
+    scores: std::collections::BTreeMap<String, f64>,
+}
+
+async fn post_rubric(url: &str, body: String, rubric: UseStateHandle<Option<RubricReport>>, error: UseStateHandle<String>) {
+    let result = Request::post(url)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .expect("Failed to create request")
+        .send()
+        .await;
+    match result {
+        Ok(resp) => match resp.json::<Option<RubricReport>>().await {
+            Ok(Some(report)) => {
+                error.set("".into());
+                rubric.set(Some(report));
+            }
+            _ => error.set("Rejected: check criterion names, weights and score ranges".into()),
+        },
+        Err(e) => error.set(format!("Request error: {:?}", e)),
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct InterviewSlot {
+    pub id: String,
+    pub start: String,
+    pub end: String,
+    pub booked_by: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AddSlotRequest {
+    session_id: String,
+    start: String,
+    end: String,
+}
+
+#[derive(Serialize)]
+struct SlotRequest {
+    session_id: String,
+    slot_id: String,
+}
+
+#[derive(Serialize)]
+struct ReviewRequest {
+    session_id: String,
+    group: String,
+    project: Option<String>,
+    status: String,
+}
+
 #[derive(Serialize)]
 struct AddPrefRequest {
     session_id: String,
     pref: String,
+    tie_with_previous: bool,
 }
 
 #[function_component(DashboardCompanyPage)]
@@ -33,7 +142,24 @@ pub fn dashboard_company_page() -> Html {
     let company = use_state(|| None::<Company>);
     let groups = use_state(|| Vec::<Group>::new());
     let new_pref = use_state(|| "".to_string());
+    let tie_with_previous = use_state(|| false);
     let error = use_state(|| "".to_string());
+    let projects = use_state(Vec::<Project>::new);
+    let project_title = use_state(|| "".to_string());
+    let project_description = use_state(|| "".to_string());
+    let project_capacity = use_state(|| "1".to_string());
+    let project_error = use_state(|| "".to_string());
+    let applications = use_state(Vec::<Application>::new);
+    let rubric = use_state(|| None::<RubricReport>);
+    let slots = use_state(Vec::<InterviewSlot>::new);
+    let slot_start = use_state(|| "".to_string());
+    let slot_end = use_state(|| "".to_string());
+    let slot_error = use_state(|| "".to_string());
+    let rubric_input = use_state(|| "".to_string());
+    let reviewer = use_state(|| "".to_string());
+    let review_group = use_state(|| "".to_string());
+    let review_scores = use_state(|| "".to_string());
+    let rubric_error = use_state(|| "".to_string());
 
     let session_id = web_sys::window()
         .unwrap().location().search().unwrap_or_default()
@@ -78,9 +204,336 @@ pub fn dashboard_company_page() -> Html {
         });
     }
 
+    let refresh_projects = {
+        let projects = projects.clone();
+
+        Callback::from(move |_| {
+            let projects = projects.clone();
+
+            spawn_local(async move {
+                if let Ok(resp) = Request::get("http://localhost:3000/project/list").send().await
+                    && let Ok(list) = resp.json::<Vec<Project>>().await
+                {
+                    projects.set(list);
+                }
+            });
+        })
+    };
+    {
+        let refresh_projects = refresh_projects.clone();
+        use_effect_with((), move |_| {
+            refresh_projects.emit(());
+            || ()
+        });
+    }
+
+    let refresh_applications = {
+        let applications = applications.clone();
+        let session_id = session_id.clone();
+
+        Callback::from(move |_| {
+            let applications = applications.clone();
+            let url = format!("http://localhost:3000/company/applications?session_id={}", session_id);
+
+            spawn_local(async move {
+                if let Ok(resp) = Request::get(&url).send().await
+                    && let Ok(list) = resp.json::<Vec<Application>>().await
+                {
+                    applications.set(list);
+                }
+            });
+        })
+    };
+    {
+        let refresh_applications = refresh_applications.clone();
+        use_effect_with((), move |_| {
+            refresh_applications.emit(());
+            || ()
+        });
+    }
+
+    {
+        let rubric = rubric.clone();
+        let session_id = session_id.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                let url = format!("http://localhost:3000/company/rubric?session_id={}", session_id);
+                if let Ok(resp) = Request::get(&url).send().await
+                    && let Ok(data) = resp.json::<Option<RubricReport>>().await
+                {
+                    rubric.set(data);
+                }
+            });
+            || ()
+        });
+    }
+
+    // Kryteria w formacie "nazwa:waga:max, ..."
+    let on_set_rubric = {
+        let rubric = rubric.clone();
+        let rubric_input = rubric_input.clone();
+        let rubric_error = rubric_error.clone();
+        let session_id = session_id.clone();
+        let refresh_company = refresh_company.clone();
+
+        Callback::from(move |_| {
+            let criteria: Option<Vec<Criterion>> = rubric_input.split(',')
+                .map(|c| c.trim())
+                .filter(|c| !c.is_empty())
+                .map(|c| {
+                    let parts: Vec<&str> = c.split(':').map(|p| p.trim()).collect();
+                    match parts.as_slice() {
+                        [name, weight, max] => Some(Criterion {
+                            name: name.to_string(),
+                            weight: weight.parse().ok()?,
+                            max: max.parse().ok()?,
+                        }),
+                        _ => None,
+                    }
+                })
+                .collect();
+            let Some(criteria) = criteria else {
+                rubric_error.set("Use name:weight:max, separated by commas".into());
+                return;
+            };
+
+            let request = SetRubricRequest { session_id: session_id.clone(), criteria };
+            let body = serde_json::to_string(&request).unwrap();
+            let (rubric, rubric_error, refresh_company) = (rubric.clone(), rubric_error.clone(), refresh_company.clone());
+            spawn_local(async move {
+                post_rubric("http://localhost:3000/company/rubric", body, rubric, rubric_error).await;
+                refresh_company.emit(());
+            });
+        })
+    };
+
+    // Oceny w formacie "kryterium=ocena, ..."; puste pole usuwa ocenę recenzenta
+    let on_score = {
+        let rubric = rubric.clone();
+        let reviewer = reviewer.clone();
+        let review_group = review_group.clone();
+        let review_scores = review_scores.clone();
+        let rubric_error = rubric_error.clone();
+        let session_id = session_id.clone();
+        let refresh_company = refresh_company.clone();
+
+        Callback::from(move |_| {
+            let scores: Option<std::collections::BTreeMap<String, f64>> = review_scores.split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    let (name, score) = s.split_once('=')?;
+                    Some((name.trim().to_string(), score.trim().parse().ok()?))
+                })
+                .collect();
+            let Some(scores) = scores else {
+                rubric_error.set("Use criterion=score, separated by commas".into());
+                return;
+            };
+
+            let request = ScoreRequest {
+                session_id: session_id.clone(),
+                reviewer: (*reviewer).trim().to_string(),
+                group: (*review_group).trim().to_string(),
+                scores,
+            };
+            let body = serde_json::to_string(&request).unwrap();
+            let (rubric, rubric_error, refresh_company) = (rubric.clone(), rubric_error.clone(), refresh_company.clone());
+            spawn_local(async move {
+                post_rubric("http://localhost:3000/company/score", body, rubric, rubric_error).await;
+                refresh_company.emit(());
+            });
+        })
+    };
+
+    let refresh_slots = {
+        let slots = slots.clone();
+        let session_id = session_id.clone();
+
+        Callback::from(move |_| {
+            let slots = slots.clone();
+            let url = format!("http://localhost:3000/company/schedule?session_id={}", session_id);
+
+            spawn_local(async move {
+                if let Ok(resp) = Request::get(&url).send().await
+                    && let Ok(list) = resp.json::<Vec<InterviewSlot>>().await
+                {
+                    slots.set(list);
+                }
+            });
+        })
+    };
+    {
+        let refresh_slots = refresh_slots.clone();
+        use_effect_with((), move |_| {
+            refresh_slots.emit(());
+            || ()
+        });
+    }
+
+    let on_add_slot = {
+        let slot_start = slot_start.clone();
+        let slot_end = slot_end.clone();
+        let slot_error = slot_error.clone();
+        let session_id = session_id.clone();
+        let refresh_slots = refresh_slots.clone();
+
+        Callback::from(move |_| {
+            let request = AddSlotRequest {
+                session_id: session_id.clone(),
+                start: (*slot_start).clone(),
+                end: (*slot_end).clone(),
+            };
+            let body = serde_json::to_string(&request).unwrap();
+            let (slot_error, refresh_slots) = (slot_error.clone(), refresh_slots.clone());
+
+            spawn_local(async move {
+                let result = Request::post("http://localhost:3000/company/slots")
+                    .header("Content-Type", "application/json")
+                    .body(body)
+                    .expect("Failed to create request")
+                    .send()
+                    .await;
+                match result {
+                    Ok(resp) => match resp.json::<Option<InterviewSlot>>().await {
+                        Ok(Some(_)) => {
+                            slot_error.set("".into());
+                            refresh_slots.emit(());
+                        }
+                        _ => slot_error.set("Invalid time or overlapping slot".into()),
+                    },
+                    Err(e) => slot_error.set(format!("Request error: {:?}", e)),
+                }
+            });
+        })
+    };
+
+    let on_remove_slot = {
+        let session_id = session_id.clone();
+        let refresh_slots = refresh_slots.clone();
+
+        move |slot_id: &str| {
+            let request = SlotRequest { session_id: session_id.clone(), slot_id: slot_id.to_string() };
+            let refresh_slots = refresh_slots.clone();
+
+            Callback::from(move |_| {
+                let body = serde_json::to_string(&request).unwrap();
+                let refresh_slots = refresh_slots.clone();
+
+                spawn_local(async move {
+                    let result = Request::post("http://localhost:3000/company/slots/remove")
+                        .header("Content-Type", "application/json")
+                        .body(body)
+                        .expect("Failed to create request")
+                        .send()
+                        .await;
+                    if result.is_ok() {
+                        refresh_slots.emit(());
+                    }
+                });
+            })
+        }
+    };
+
+    let on_review = {
+        let session_id = session_id.clone();
+        let refresh_applications = refresh_applications.clone();
+
+        move |application: &Application, status: &str| {
+            let request = ReviewRequest {
+                session_id: session_id.clone(),
+                group: application.group.clone(),
+                project: application.project.clone(),
+                status: status.to_string(),
+            };
+            let refresh_applications = refresh_applications.clone();
+
+            Callback::from(move |_| {
+                let body = serde_json::to_string(&request).unwrap();
+                let refresh_applications = refresh_applications.clone();
+
+                spawn_local(async move {
+                    let result = Request::post("http://localhost:3000/company/review")
+                        .header("Content-Type", "application/json")
+                        .body(body)
+                        .expect("Failed to create request")
+                        .send()
+                        .await;
+                    if result.is_ok() {
+                        refresh_applications.emit(());
+                    }
+                });
+            })
+        }
+    };
+
+    let on_add_project = {
+        let project_title = project_title.clone();
+        let project_description = project_description.clone();
+        let project_capacity = project_capacity.clone();
+        let project_error = project_error.clone();
+        let session_id = session_id.clone();
+        let refresh_projects = refresh_projects.clone();
+
+        Callback::from(move |_| {
+            let title = (*project_title).trim().to_string();
+            let description = (*project_description).trim().to_string();
+
+            if title.is_empty() {
+                project_error.set("Project title cannot be empty".into());
+                return;
+            }
+
+            let capacity = match (*project_capacity).trim().parse::<usize>() {
+                Ok(c) if c > 0 => c,
+                _ => {
+                    project_error.set("Capacity must be a positive number".into());
+                    return;
+                }
+            };
+
+            project_title.set("".into());
+            project_description.set("".into());
+            project_capacity.set("1".into());
+            project_error.set("".into());
+
+            let session = session_id.clone();
+            let refresh_projects = refresh_projects.clone();
+
+            spawn_local(async move {
+                let request = AddProjectRequest {
+                    session_id: session,
+                    title,
+                    description,
+                    capacity,
+                };
+                let result = Request::post("http://localhost:3000/company/add_project")
+                    .header("Content-Type", "application/json")
+                    .body(serde_json::to_string(&request).unwrap())
+                    .expect("Failed to create request")
+                    .send()
+                    .await;
+
+                match result {
+                    Ok(resp) => {
+                        if resp.status() == 200 {
+                            refresh_projects.emit(());
+                        } else {
+                            web_sys::console::error_1(&"Failed to add project".into());
+                        }
+                    }
+                    Err(e) => {
+                        web_sys::console::error_1(&format!("Request error: {:?}", e).into());
+                    }
+                }
+            });
+        })
+    };
+
     let on_add_pref = {
         let company = company.clone();
         let new_pref = new_pref.clone();
+        let tie_with_previous = tie_with_previous.clone();
         let error = error.clone();
         let session_id = session_id.clone();
         let groups = groups.clone();
@@ -106,7 +559,9 @@ pub fn dashboard_company_page() -> Html {
                 }
             }
 
+            let tie = *tie_with_previous;
             new_pref.set("".into());
+            tie_with_previous.set(false);
             error.set("".into());
 
             let session = session_id.clone();
@@ -116,6 +571,7 @@ pub fn dashboard_company_page() -> Html {
                 let request = AddPrefRequest {
                     session_id: session,
                     pref: pref.clone(),
+                    tie_with_previous: tie,
                 };
                 let result = Request::post("http://localhost:3000/company/add_pref")
                     .header("Content-Type", "application/json")
@@ -140,6 +596,15 @@ pub fn dashboard_company_page() -> Html {
         })
     };
 
+    let on_tie_toggle = {
+        let tie_with_previous = tie_with_previous.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            tie_with_previous.set(input.checked());
+        })
+    };
+
     let on_input = {
         let error = error.clone();
         let new_pref = new_pref.clone();
@@ -153,6 +618,13 @@ pub fn dashboard_company_page() -> Html {
         })
     };
 
+    let on_project_input = |field: UseStateHandle<String>| {
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            field.set(input.value());
+        })
+    };
+
     html! {
         <div class="dashboard-common dashboard-company">
             <h1>{ "Company Dashboard" }</h1>
@@ -171,10 +643,14 @@ pub fn dashboard_company_page() -> Html {
                                 <p><i>{ "No preferences added yet" }</i></p>
                             } else {
                                 <ul class="preferences-list">
-                                    { for c.preferences.iter().map(|p| html!{ 
-                                        <li key={p.clone()}>
-                                            <span>{p}</span>
-                                        </li>
+                                    { for c.preferences.iter().enumerate().map(|(i, p)| {
+                                        // Pozycje w remisie mają ten sam numer
+                                        let rank = if c.tiers.len() == c.preferences.len() { c.tiers[i] + 1 } else { i + 1 };
+                                        html!{
+                                            <li key={p.clone()}>
+                                                <span>{ format!("#{} {}", rank, p) }</span>
+                                            </li>
+                                        }
                                     }) }
                                 </ul>
                             }
@@ -190,6 +666,14 @@ pub fn dashboard_company_page() -> Html {
                                     oninput={on_input}
                                     class={if !error.is_empty() { "input-error" } else { "" }}
                                 />
+                                <label class="tie-option">
+                                    <input
+                                        type="checkbox"
+                                        checked={*tie_with_previous}
+                                        onchange={on_tie_toggle}
+                                    />
+                                    { "Tied with previous" }
+                                </label>
                                 <button 
                                     onclick={on_add_pref}
                                     disabled={session_id.is_empty()}
@@ -210,6 +694,193 @@ pub fn dashboard_company_page() -> Html {
                             }
                         </div>
 
+                        <div class="preferences-section">
+                            <h3>{ "Projects" }</h3>
+                            { {
+                                let own: Vec<&Project> = projects.iter()
+                                    .filter(|p| p.company == c.name)
+                                    .collect();
+                                if own.is_empty() {
+                                    html!{ <p><i>{ "No projects added yet" }</i></p> }
+                                } else {
+                                    html!{
+                                        <ul class="preferences-list">
+                                            { for own.iter().map(|p| html!{
+                                                <li key={p.id.clone()}>
+                                                    <span>{ format!("{} (capacity: {})", p.title, p.capacity) }</span>
+                                                    if !p.description.is_empty() {
+                                                        <span class="project">{ format!(" – {}", p.description) }</span>
+                                                    }
+                                                </li>
+                                            }) }
+                                        </ul>
+                                    }
+                                }
+                            } }
+                            <div class="input-group">
+                                <input
+                                    type="text"
+                                    value={(*project_title).clone()}
+                                    placeholder="Project title"
+                                    oninput={on_project_input(project_title.clone())}
+                                />
+                                <input
+                                    type="text"
+                                    value={(*project_description).clone()}
+                                    placeholder="Description"
+                                    oninput={on_project_input(project_description.clone())}
+                                />
+                                <input
+                                    type="number"
+                                    min="1"
+                                    value={(*project_capacity).clone()}
+                                    oninput={on_project_input(project_capacity.clone())}
+                                />
+                                <button
+                                    onclick={on_add_project}
+                                    disabled={session_id.is_empty()}
+                                    class="btn btn-success"
+                                >
+                                    { "Add Project" }
+                                </button>
+                            </div>
+                            if !project_error.is_empty() {
+                                <div class="error-message">
+                                    { (*project_error).clone() }
+                                </div>
+                            }
+                        </div>
+
+                        <div class="preferences-section">
+                            <h3>{ "Interview Slots" }</h3>
+                            if slots.is_empty() {
+                                <p><i>{ "No slots published yet" }</i></p>
+                            } else {
+                                <ul class="preferences-list">
+                                    { for slots.iter().map(|slot| html!{
+                                        <li key={slot.id.clone()}>
+                                            <span>{ format!("{} – {}", slot.start, slot.end) }</span>
+                                            <span class="project">{ match &slot.booked_by {
+                                                Some(group) => format!(" booked by {}", group),
+                                                None => " free".to_string(),
+                                            } }</span>
+                                            <button class="btn" onclick={on_remove_slot(&slot.id)}>{ "Remove" }</button>
+                                        </li>
+                                    }) }
+                                </ul>
+                            }
+                            <div class="input-group">
+                                <input
+                                    type="datetime-local"
+                                    value={(*slot_start).clone()}
+                                    oninput={on_project_input(slot_start.clone())}
+                                />
+                                <input
+                                    type="datetime-local"
+                                    value={(*slot_end).clone()}
+                                    oninput={on_project_input(slot_end.clone())}
+                                />
+                                <button onclick={on_add_slot} disabled={session_id.is_empty()} class="btn btn-success">
+                                    { "Publish Slot" }
+                                </button>
+                            </div>
+                            if !slot_error.is_empty() {
+                                <div class="error-message">
+                                    { (*slot_error).clone() }
+                                </div>
+                            }
+                            <a href={format!("http://localhost:3000/company/schedule.ics?session_id={}", session_id)}>
+                                { "Export calendar (ICS)" }
+                            </a>
+                        </div>
+
+                        <div class="preferences-section">
+                            <h3>{ "Scoring Rubric" }</h3>
+                            <p><i>{ "Once groups are scored, scored groups lead your list; unscored groups from your own list follow in your order." }</i></p>
+                            { if let Some(report) = (*rubric).clone() {
+                                html!{
+                                    <>
+                                        <p>{ report.criteria.iter()
+                                            .map(|c| format!("{} (weight {}, max {})", c.name, c.weight, c.max))
+                                            .collect::<Vec<_>>()
+                                            .join(", ") }</p>
+                                        <ul class="preferences-list">
+                                            { for report.scores.iter().map(|s| html!{
+                                                <li key={s.group.clone()}>
+                                                    <span>{ format!("{}: {:.0}%", s.group, s.total * 100.0) }</span>
+                                                </li>
+                                            }) }
+                                        </ul>
+                                    </>
+                                }
+                            } else {
+                                html!{}
+                            }}
+                            <div class="input-group">
+                                <input
+                                    type="text"
+                                    value={(*rubric_input).clone()}
+                                    placeholder="experience:2:5, motivation:1:5"
+                                    oninput={on_project_input(rubric_input.clone())}
+                                />
+                                <button onclick={on_set_rubric} disabled={session_id.is_empty()} class="btn btn-success">
+                                    { "Set Rubric" }
+                                </button>
+                            </div>
+                            <div class="input-group">
+                                <input
+                                    type="text"
+                                    value={(*reviewer).clone()}
+                                    placeholder="Reviewer"
+                                    oninput={on_project_input(reviewer.clone())}
+                                />
+                                <input
+                                    type="text"
+                                    value={(*review_group).clone()}
+                                    placeholder="Group"
+                                    oninput={on_project_input(review_group.clone())}
+                                />
+                                <input
+                                    type="text"
+                                    value={(*review_scores).clone()}
+                                    placeholder="experience=4, motivation=5"
+                                    oninput={on_project_input(review_scores.clone())}
+                                />
+                                <button onclick={on_score} disabled={session_id.is_empty()} class="btn btn-success">
+                                    { "Score" }
+                                </button>
+                            </div>
+                            if !rubric_error.is_empty() {
+                                <div class="error-message">
+                                    { (*rubric_error).clone() }
+                                </div>
+                            }
+                        </div>
+
+                        <div class="preferences-section">
+                            <h3>{ "Applications" }</h3>
+                            if applications.is_empty() {
+                                <p><i>{ "No applications yet" }</i></p>
+                            } else {
+                                <ul class="preferences-list">
+                                    { for applications.iter().map(|a| html!{
+                                        <li key={format!("{}-{:?}", a.group, a.project)}>
+                                            <span>
+                                                <strong>{ &a.group }</strong>
+                                                { a.project.as_ref().map(|p| format!(" → {}", p)).unwrap_or_default() }
+                                                { format!(" [{}]", a.status) }
+                                            </span>
+                                            if !a.motivation.is_empty() {
+                                                <span class="project">{ format!(" – {}", a.motivation) }</span>
+                                            }
+                                            <button class="btn btn-success" onclick={on_review(a, "shortlisted")}>{ "Shortlist" }</button>
+                                            <button class="btn" onclick={on_review(a, "rejected")}>{ "Reject" }</button>
+                                        </li>
+                                    }) }
+                                </ul>
+                            }
+                        </div>
+
                         <div class="available-list">
                             <h3>{ "Available Groups" }</h3>
                             if groups.is_empty() {
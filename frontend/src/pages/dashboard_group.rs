@@ -4,7 +4,7 @@ use crate::Route;
 use gloo_net::http::Request;
 use serde::{Serialize, Deserialize};
 use wasm_bindgen_futures::spawn_local;
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Group {
@@ -12,6 +12,8 @@ pub struct Group {
     pub email: String,
     pub password: String,
     pub preferences: Vec<String>,
+    #[serde(default)]
+    pub tiers: Vec<usize>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -22,10 +24,103 @@ pub struct Company {
     pub preferences: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Project {
+    pub id: String,
+    pub company: String,
+    pub title: String,
+    pub description: String,
+    pub capacity: usize,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AddPrefRequest {
     pub session_id: String,
     pub pref: String,
+    pub tie_with_previous: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MemberAgreement {
+    pub member: String,
+    pub preferences: Vec<String>,
+    pub agreement: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AggregationReport {
+    pub rule: String,
+    pub preferences: Vec<String>,
+    pub tiers: Vec<usize>,
+    pub members: Vec<MemberAgreement>,
+}
+
+#[derive(Serialize)]
+struct SubmitRankingRequest {
+    pub session_id: String,
+    pub preferences: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct SetAggregationRequest {
+    pub session_id: String,
+    pub rule: String,
+}
+
+// Wysyła zmianę rankingu członka albo reguły; odpowiedzią jest nowy agregat
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Application {
+    pub company: String,
+    pub project: Option<String>,
+    pub motivation: String,
+    pub status: String,
+}
+
+#[derive(Serialize)]
+struct ApplyRequest {
+    session_id: String,
+    company: String,
+    project: Option<String>,
+    motivation: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InterviewSlot {
+    pub id: String,
+    pub company: String,
+    pub start: String,
+    pub end: String,
+    pub booked_by: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SlotRequest {
+    session_id: String,
+    slot_id: String,
+}
+
+#[derive(Deserialize)]
+struct BookingResponse {
+    ok: bool,
+    message: String,
+}
+
+async fn post_aggregation(url: &str, body: String, report: UseStateHandle<Option<AggregationReport>>, error: UseStateHandle<String>) {
+    let result = Request::post(url)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .expect("Failed to create request")
+        .send()
+        .await;
+
+    match result {
+        Ok(resp) => match resp.json::<Option<AggregationReport>>().await {
+            Ok(Some(data)) => report.set(Some(data)),
+            Ok(None) => error.set("Rejected: not a group member, unknown company/project, or too many companies for Kemeny-Young".into()),
+            Err(e) => error.set(format!("Failed to parse: {}", e)),
+        },
+        Err(e) => error.set(format!("Request error: {:?}", e)),
+    }
 }
 
 #[function_component(DashboardGroupPage)]
@@ -33,7 +128,20 @@ pub fn dashboard_group_page() -> Html {
     let group = use_state(|| None::<Group>);
     let companies = use_state(|| Vec::<Company>::new());
     let new_pref = use_state(|| "".to_string());
+    let tie_with_previous = use_state(|| false);
     let error = use_state(|| "".to_string());
+    let projects = use_state(Vec::<Project>::new);
+    let aggregation = use_state(|| None::<AggregationReport>);
+    let member_prefs = use_state(|| "".to_string());
+    let member_error = use_state(|| "".to_string());
+    let applications = use_state(Vec::<Application>::new);
+    let schedule = use_state(Vec::<InterviewSlot>::new);
+    let open_slots = use_state(Vec::<InterviewSlot>::new);
+    let booking_message = use_state(|| "".to_string());
+    let apply_company = use_state(|| "".to_string());
+    let apply_project = use_state(|| "".to_string());
+    let apply_motivation = use_state(|| "".to_string());
+    let apply_error = use_state(|| "".to_string());
 
     let session_id = web_sys::window()
         .unwrap().location().search().unwrap_or_default()
@@ -79,9 +187,240 @@ pub fn dashboard_group_page() -> Html {
             || ()
         });
     }
+    {
+        let projects = projects.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                if let Ok(resp) = Request::get("http://localhost:3000/project/list").send().await
+                    && let Ok(list) = resp.json::<Vec<Project>>().await
+                {
+                    projects.set(list);
+                }
+            });
+            || ()
+        });
+    }
+    {
+        let aggregation = aggregation.clone();
+        let session_id = session_id.clone();
+        use_effect_with(session_id.clone(), move |_| {
+            spawn_local(async move {
+                let url = format!("http://localhost:3000/group/aggregation?session_id={}", session_id);
+                if let Ok(resp) = Request::get(&url).send().await
+                    && let Ok(data) = resp.json::<Option<AggregationReport>>().await
+                {
+                    aggregation.set(data);
+                }
+            });
+            || ()
+        });
+    }
+
+    let refresh_applications = {
+        let applications = applications.clone();
+        let session_id = session_id.clone();
+
+        Callback::from(move |_| {
+            let applications = applications.clone();
+            let url = format!("http://localhost:3000/group/applications?session_id={}", session_id);
+
+            spawn_local(async move {
+                if let Ok(resp) = Request::get(&url).send().await
+                    && let Ok(list) = resp.json::<Vec<Application>>().await
+                {
+                    applications.set(list);
+                }
+            });
+        })
+    };
+    {
+        let refresh_applications = refresh_applications.clone();
+        use_effect_with((), move |_| {
+            refresh_applications.emit(());
+            || ()
+        });
+    }
+
+    let refresh_schedule = {
+        let schedule = schedule.clone();
+        let open_slots = open_slots.clone();
+        let session_id = session_id.clone();
+
+        Callback::from(move |_| {
+            let schedule = schedule.clone();
+            let open_slots = open_slots.clone();
+            let url = format!("http://localhost:3000/group/schedule?session_id={}", session_id);
+
+            spawn_local(async move {
+                if let Ok(resp) = Request::get(&url).send().await
+                    && let Ok(list) = resp.json::<Vec<InterviewSlot>>().await
+                {
+                    schedule.set(list);
+                }
+                if let Ok(resp) = Request::get("http://localhost:3000/interview/slots").send().await
+                    && let Ok(list) = resp.json::<Vec<InterviewSlot>>().await
+                {
+                    open_slots.set(list);
+                }
+            });
+        })
+    };
+    {
+        let refresh_schedule = refresh_schedule.clone();
+        use_effect_with((), move |_| {
+            refresh_schedule.emit(());
+            || ()
+        });
+    }
+
+    // Rezerwacja albo odwołanie terminu
+    let on_slot = {
+        let booking_message = booking_message.clone();
+        let session_id = session_id.clone();
+        let refresh_schedule = refresh_schedule.clone();
+
+        move |slot_id: &str, book: bool| {
+            let request = SlotRequest { session_id: session_id.clone(), slot_id: slot_id.to_string() };
+            let (booking_message, refresh_schedule) = (booking_message.clone(), refresh_schedule.clone());
+
+            Callback::from(move |_| {
+                let body = serde_json::to_string(&request).unwrap();
+                let (booking_message, refresh_schedule) = (booking_message.clone(), refresh_schedule.clone());
+                let url = if book { "http://localhost:3000/group/book" } else { "http://localhost:3000/group/cancel" };
+
+                spawn_local(async move {
+                    let result = Request::post(url)
+                        .header("Content-Type", "application/json")
+                        .body(body)
+                        .expect("Failed to create request")
+                        .send()
+                        .await;
+                    if let Ok(resp) = result {
+                        if book {
+                            match resp.json::<BookingResponse>().await {
+                                Ok(booking) if !booking.ok => booking_message.set(booking.message),
+                                _ => booking_message.set("".into()),
+                            }
+                        }
+                        refresh_schedule.emit(());
+                    }
+                });
+            })
+        }
+    };
+
+    let on_apply = {
+        let apply_company = apply_company.clone();
+        let apply_project = apply_project.clone();
+        let apply_motivation = apply_motivation.clone();
+        let apply_error = apply_error.clone();
+        let session_id = session_id.clone();
+        let refresh_applications = refresh_applications.clone();
+
+        Callback::from(move |_| {
+            let company = (*apply_company).trim().to_string();
+            if company.is_empty() {
+                apply_error.set("Company cannot be empty".into());
+                return;
+            }
+            let project = Some((*apply_project).trim().to_string()).filter(|p| !p.is_empty());
+            let request = ApplyRequest {
+                session_id: session_id.clone(),
+                company,
+                project,
+                motivation: (*apply_motivation).trim().to_string(),
+            };
+            let body = serde_json::to_string(&request).unwrap();
+            let (apply_error, refresh_applications) = (apply_error.clone(), refresh_applications.clone());
+            apply_motivation.set("".into());
+
+            spawn_local(async move {
+                let result = Request::post("http://localhost:3000/group/apply")
+                    .header("Content-Type", "application/json")
+                    .body(body)
+                    .expect("Failed to create request")
+                    .send()
+                    .await;
+                match result {
+                    Ok(resp) => match resp.json::<Option<Application>>().await {
+                        Ok(Some(_)) => {
+                            apply_error.set("".into());
+                            refresh_applications.emit(());
+                        }
+                        _ => apply_error.set("Application rejected (unknown company/project, already reviewed or motivation too long)".into()),
+                    },
+                    Err(e) => apply_error.set(format!("Request error: {:?}", e)),
+                }
+            });
+        })
+    };
+
+    let on_apply_input = |field: UseStateHandle<String>| {
+        Callback::from(move |e: InputEvent| {
+            field.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let on_submit_ranking = {
+        let aggregation = aggregation.clone();
+        let member_prefs = member_prefs.clone();
+        let member_error = member_error.clone();
+        let session_id = session_id.clone();
+        let refresh_group = refresh_group.clone();
+
+        Callback::from(move |_| {
+            let preferences: Vec<String> = member_prefs.split(',')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect();
+            member_error.set("".into());
+
+            let request = SubmitRankingRequest { session_id: session_id.clone(), preferences };
+            let body = serde_json::to_string(&request).unwrap();
+            let (aggregation, member_error, refresh_group) = (aggregation.clone(), member_error.clone(), refresh_group.clone());
+            spawn_local(async move {
+                post_aggregation("http://localhost:3000/group/member_ranking", body, aggregation, member_error).await;
+                refresh_group.emit(());
+            });
+        })
+    };
+
+    let on_rule_change = {
+        let aggregation = aggregation.clone();
+        let member_error = member_error.clone();
+        let session_id = session_id.clone();
+        let refresh_group = refresh_group.clone();
+
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            let request = SetAggregationRequest { session_id: session_id.clone(), rule: select.value() };
+            let body = serde_json::to_string(&request).unwrap();
+            let (aggregation, member_error, refresh_group) = (aggregation.clone(), member_error.clone(), refresh_group.clone());
+            spawn_local(async move {
+                post_aggregation("http://localhost:3000/group/aggregation", body, aggregation, member_error).await;
+                refresh_group.emit(());
+            });
+        })
+    };
+
+    let on_member_prefs_input = {
+        let member_prefs = member_prefs.clone();
+        Callback::from(move |e: InputEvent| {
+            member_prefs.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let rules = [
+        ("borda", "Borda count"),
+        ("copeland", "Copeland (pairwise majorities)"),
+        ("kemeny_young", "Kemeny-Young (small lists)"),
+    ];
+
     let on_add_pref = {
         let group = group.clone();
+        let projects = projects.clone();
         let new_pref = new_pref.clone();
+        let tie_with_previous = tie_with_previous.clone();
         let error = error.clone();
         let companies = companies.clone();
         let session_id = session_id.clone();
@@ -95,8 +434,8 @@ pub fn dashboard_group_page() -> Html {
                 return;
             }
 
-            if !companies.iter().any(|c| c.name == pref) {
-                error.set(format!("Company '{}' does not exist!", pref));
+            if !companies.iter().any(|c| c.name == pref) && !projects.iter().any(|p| p.id == pref) {
+                error.set(format!("Company or project '{}' does not exist!", pref));
                 return;
             }
             if let Some(g) = &*group {
@@ -106,16 +445,20 @@ pub fn dashboard_group_page() -> Html {
                 }
             }
 
+            let tie = *tie_with_previous;
             new_pref.set("".into());
+            tie_with_previous.set(false);
             error.set("".into());
 
             let session = session_id.clone();
             let refresh_group = refresh_group.clone();
+            let error = error.clone();
             
             spawn_local(async move {
                 let request = AddPrefRequest {
                     session_id: session,
                     pref: pref.clone(),
+                    tie_with_previous: tie,
                 };
 
                 let result = Request::post("http://localhost:3000/group/add_pref")
@@ -127,10 +470,12 @@ pub fn dashboard_group_page() -> Html {
 
                 match result {
                     Ok(resp) => {
-                        if resp.status() == 200 {
+                        if resp.status() != 200 {
+                            web_sys::console::error_1(&"Failed to add preference".into());
+                        } else if resp.json::<bool>().await.unwrap_or(false) {
                             refresh_group.emit(());
                         } else {
-                            web_sys::console::error_1(&"Failed to add preference".into());
+                            error.set("Rejected: the list is the aggregate of member rankings, or the group size is outside the limits".into());
                         }
                     }
                     Err(e) => {
@@ -141,6 +486,15 @@ pub fn dashboard_group_page() -> Html {
         })
     };
 
+    let on_tie_toggle = {
+        let tie_with_previous = tie_with_previous.clone();
+
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            tie_with_previous.set(input.checked());
+        })
+    };
+
     let on_input = {
         let error = error.clone();
         let new_pref = new_pref.clone();
@@ -172,13 +526,58 @@ pub fn dashboard_group_page() -> Html {
                                 <p><i>{ "No preferences added yet. Add companies you're interested in!" }</i></p>
                             } else {
                                 <ul class="preferences-list">
-                                    { for g.preferences.iter().map(|p| html!{ 
-                                        <li key={p.clone()}>
-                                            <span>{p}</span>
+                                    { for g.preferences.iter().enumerate().map(|(i, p)| {
+                                        // Pozycje w remisie mają ten sam numer
+                                        let rank = if g.tiers.len() == g.preferences.len() { g.tiers[i] + 1 } else { i + 1 };
+                                        html!{
+                                            <li key={p.clone()}>
+                                                <span>{ format!("#{} {}", rank, p) }</span>
+                                            </li>
+                                        }
+                                    }) }
+                                </ul>
+                            }
+                        </div>
+
+                        <div class="preferences-section">
+                            <h3>{ "Member Rankings" }</h3>
+                            <p><i>{ "Each member logged in with their student account can submit a ranking; the group list above is their aggregate." }</i></p>
+                            <div class="input-group">
+                                <select onchange={on_rule_change}>
+                                    { for rules.iter().map(|(value, label)| html! {
+                                        <option value={*value} selected={aggregation.as_ref().is_some_and(|a| a.rule == *value)}>{ *label }</option>
+                                    }) }
+                                </select>
+                            </div>
+                            if let Some(report) = (*aggregation).clone() {
+                                <ul class="preferences-list">
+                                    { for report.members.iter().map(|m| html!{
+                                        <li key={m.member.clone()}>
+                                            <span>{ format!("{}: {} (agreement {:.0}%)", m.member, m.preferences.join(" > "), m.agreement * 100.0) }</span>
                                         </li>
                                     }) }
                                 </ul>
                             }
+                            <div class="input-group">
+                                <input
+                                    type="text"
+                                    value={(*member_prefs).clone()}
+                                    placeholder="Your ranking, comma separated (empty removes it)"
+                                    oninput={on_member_prefs_input}
+                                />
+                                <button
+                                    onclick={on_submit_ranking}
+                                    disabled={session_id.is_empty()}
+                                    class="btn btn-success"
+                                >
+                                    { "Submit ranking" }
+                                </button>
+                            </div>
+                            if !member_error.is_empty() {
+                                <div class="error-message">
+                                    { (*member_error).clone() }
+                                </div>
+                            }
                         </div>
 
                         <div class="preferences-section">
@@ -187,10 +586,18 @@ pub fn dashboard_group_page() -> Html {
                                 <input
                                     type="text"
                                     value={(*new_pref).clone()}
-                                    placeholder="Enter company name"
+                                    placeholder="Enter company name or project id"
                                     oninput={on_input}
                                     class={if !error.is_empty() { "input-error" } else { "" }}
                                 />
+                                <label class="tie-option">
+                                    <input
+                                        type="checkbox"
+                                        checked={*tie_with_previous}
+                                        onchange={on_tie_toggle}
+                                    />
+                                    { "Tied with previous" }
+                                </label>
                                 <button 
                                     onclick={on_add_pref}
                                     disabled={session_id.is_empty()}
@@ -211,6 +618,93 @@ pub fn dashboard_group_page() -> Html {
                             }
                         </div>
 
+                        <div class="preferences-section">
+                            <h3>{ "Interviews" }</h3>
+                            if schedule.is_empty() {
+                                <p><i>{ "No interviews booked yet" }</i></p>
+                            } else {
+                                <ul class="preferences-list">
+                                    { for schedule.iter().map(|slot| html!{
+                                        <li key={slot.id.clone()}>
+                                            <span>{ format!("{}: {} – {}", slot.company, slot.start, slot.end) }</span>
+                                            <button class="btn" onclick={on_slot(&slot.id, false)}>{ "Cancel" }</button>
+                                        </li>
+                                    }) }
+                                </ul>
+                            }
+                            <h4>{ "Open slots" }</h4>
+                            if open_slots.is_empty() {
+                                <p><i>{ "No open slots" }</i></p>
+                            } else {
+                                <ul class="preferences-list">
+                                    { for open_slots.iter().map(|slot| html!{
+                                        <li key={slot.id.clone()}>
+                                            <span>{ format!("{}: {} – {}", slot.company, slot.start, slot.end) }</span>
+                                            <button class="btn btn-success" onclick={on_slot(&slot.id, true)}>{ "Book" }</button>
+                                        </li>
+                                    }) }
+                                </ul>
+                            }
+                            if !booking_message.is_empty() {
+                                <div class="error-message">
+                                    { (*booking_message).clone() }
+                                </div>
+                            }
+                            <a href={format!("http://localhost:3000/group/schedule.ics?session_id={}", session_id)}>
+                                { "Export calendar (ICS)" }
+                            </a>
+                        </div>
+
+                        <div class="preferences-section">
+                            <h3>{ "Applications" }</h3>
+                            if applications.is_empty() {
+                                <p><i>{ "No applications yet" }</i></p>
+                            } else {
+                                <ul class="preferences-list">
+                                    { for applications.iter().map(|a| html!{
+                                        <li key={format!("{}-{:?}", a.company, a.project)}>
+                                            <span>
+                                                <strong>{ &a.company }</strong>
+                                                { a.project.as_ref().map(|p| format!(" → {}", p)).unwrap_or_default() }
+                                                { format!(" [{}]", a.status) }
+                                            </span>
+                                            if !a.motivation.is_empty() {
+                                                <span class="project">{ format!(" – {}", a.motivation) }</span>
+                                            }
+                                        </li>
+                                    }) }
+                                </ul>
+                            }
+                            <div class="input-group">
+                                <input
+                                    type="text"
+                                    value={(*apply_company).clone()}
+                                    placeholder="Company"
+                                    oninput={on_apply_input(apply_company.clone())}
+                                />
+                                <input
+                                    type="text"
+                                    value={(*apply_project).clone()}
+                                    placeholder="Project id (optional)"
+                                    oninput={on_apply_input(apply_project.clone())}
+                                />
+                                <input
+                                    type="text"
+                                    value={(*apply_motivation).clone()}
+                                    placeholder="Short motivation"
+                                    oninput={on_apply_input(apply_motivation.clone())}
+                                />
+                                <button onclick={on_apply} disabled={session_id.is_empty()} class="btn btn-success">
+                                    { "Apply" }
+                                </button>
+                            </div>
+                            if !apply_error.is_empty() {
+                                <div class="error-message">
+                                    { (*apply_error).clone() }
+                                </div>
+                            }
+                        </div>
+
                         <div class="available-list">
                             <h3>{ "Available Companies" }</h3>
                             if companies.is_empty() {
@@ -225,6 +719,19 @@ pub fn dashboard_group_page() -> Html {
                                             } else {
                                                 html!{}
                                             }}
+                                            <ul>
+                                                { for projects.iter().filter(|p| p.company == c.name && p.id != c.name).map(|p| html!{
+                                                    <li key={p.id.clone()}>
+                                                        <span>{ format!("{} (capacity: {}) – id: {}", p.title, p.capacity, p.id) }</span>
+                                                        if !p.description.is_empty() {
+                                                            <span class="project">{ format!(" – {}", p.description) }</span>
+                                                        }
+                                                        if g.preferences.contains(&p.id) {
+                                                            <span class="already-added">{" (added)"}</span>
+                                                        }
+                                                    </li>
+                                                }) }
+                                            </ul>
                                         </li>
                                     }) }
                                 </ul>
@@ -1,6 +1,7 @@
 use yew::prelude::*;
 use gloo_net::http::Request;
 use serde::Deserialize;
+use web_sys::HtmlSelectElement;
 use yew_router::prelude::Link;
 use crate::Route;
 
@@ -8,76 +9,218 @@ use crate::Route;
 pub struct MatchResult {
     pub group: String,
     pub company: String,
+    pub project: String,
+    pub project_title: String,
+    pub group_rank: Option<usize>,
+    pub company_rank: Option<usize>,
+    #[serde(default)]
+    pub mutually_acceptable: bool,
+    #[serde(default)]
+    pub fallback: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub explanations: Vec<String>,
+}
+
+fn rank_label(rank: Option<usize>) -> String {
+    rank.map_or("unranked".to_string(), |r| format!("#{}", r))
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct TieReport {
+    pub exists: bool,
+    pub message: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RankProfile {
+    pub algorithm: String,
+    pub group_ranks: Vec<usize>,
+    pub outside_preferences: usize,
+    pub unmatched: usize,
+    pub blocking_pairs: usize,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct QuotaRelaxation {
+    pub company: String,
+    pub project: Option<String>,
+    pub minimum: usize,
+    pub achievable: usize,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct QuotaReport {
+    pub feasible: bool,
+    pub relaxations: Vec<QuotaRelaxation>,
+    pub blocking_pairs: usize,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ReservedSeats {
+    pub company: String,
+    pub track: String,
+    pub reserved: usize,
+    pub filled: usize,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ReservationReport {
+    pub seats: Vec<ReservedSeats>,
+    pub blocking_pairs: usize,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MatchResponse {
+    pub algorithm: String,
+    pub proposer: String,
+    pub seed: Option<u64>,
+    pub run_id: Option<String>,
+    pub tie_report: Option<TieReport>,
+    pub comparison: Option<Vec<RankProfile>>,
+    #[serde(default)]
+    pub unmatched: Vec<String>,
+    pub results: Vec<MatchResult>,
+    #[serde(default)]
+    pub published: bool,
+    #[serde(default)]
+    pub quotas: Option<QuotaReport>,
+    #[serde(default)]
+    pub reservations: Option<ReservationReport>,
+}
+
+async fn fetch_matches(
+    query: String,
+    response: UseStateHandle<Option<MatchResponse>>,
+    loading: UseStateHandle<bool>,
+    error: UseStateHandle<String>,
+) {
+    let url = format!("http://localhost:3000/match?{}", query);
+    match Request::get(&url).send().await {
+        Ok(resp) => {
+            if resp.ok() {
+                match resp.json::<Option<MatchResponse>>().await {
+                    Ok(Some(data)) => response.set(Some(data)),
+                    Ok(None) => error.set("These matching options cannot be combined".to_string()),
+                    Err(e) => error.set(format!("Failed to parse: {}", e)),
+                }
+            } else {
+                error.set(format!("Server error: {}", resp.status()));
+            }
+        }
+        Err(e) => {
+            error.set(format!("Network error: {}", e));
+        }
+    }
+    loading.set(false);
 }
 
 #[function_component(MatchPage)]
 pub fn match_page() -> Html {
-    let results = use_state(|| Vec::<MatchResult>::new());
+    let response = use_state(|| None::<MatchResponse>);
+    let query = use_state(|| "algorithm=gale_shapley&proposer=groups".to_string());
+    let policy = use_state(|| "leave".to_string());
+    let full_query = format!("{}&unmatched={}", *query, *policy);
     let loading = use_state(|| true);
     let error = use_state(|| "".to_string());
 
     {
-        let results = results.clone();
+        let response = response.clone();
         let loading = loading.clone();
         let error = error.clone();
 
-        use_effect_with((), move |_| {
-            wasm_bindgen_futures::spawn_local(async move {
-                match Request::get("http://localhost:3000/match").send().await {
-                    Ok(resp) => {
-                        if resp.ok() {
-                            match resp.json::<Vec<MatchResult>>().await {
-                                Ok(data) => results.set(data),
-                                Err(e) => error.set(format!("Failed to parse: {}", e)),
-                            }
-                        } else {
-                            error.set(format!("Server error: {}", resp.status()));
-                        }
-                    }
-                    Err(e) => {
-                        error.set(format!("Network error: {}", e));
-                    }
-                }
-                loading.set(false);
-            });
+        use_effect_with(full_query.clone(), move |query| {
+            loading.set(true);
+            error.set("".to_string());
+            wasm_bindgen_futures::spawn_local(fetch_matches(query.clone(), response, loading, error));
             || ()
         });
     }
 
     let refresh_matches = {
-        let results = results.clone();
+        let response = response.clone();
+        let full_query = full_query.clone();
         let loading = loading.clone();
         let error = error.clone();
 
         Callback::from(move |_| {
-            let results = results.clone();
-            let loading = loading.clone();
-            let error = error.clone();
-
             loading.set(true);
             error.set("".to_string());
 
-            wasm_bindgen_futures::spawn_local(async move {
-                match Request::get("http://localhost:3000/match").send().await {
-                    Ok(resp) => {
-                        if resp.ok() {
-                            match resp.json::<Vec<MatchResult>>().await {
-                                Ok(data) => results.set(data),
-                                Err(e) => error.set(format!("Failed to parse: {}", e)),
-                            }
-                        } else {
-                            error.set(format!("Server error: {}", resp.status()));
-                        }
-                    }
-                    Err(e) => {
-                        error.set(format!("Network error: {}", e));
-                    }
-                }
-                loading.set(false);
-            });
+            wasm_bindgen_futures::spawn_local(fetch_matches(
+                full_query.clone(),
+                response.clone(),
+                loading.clone(),
+                error.clone(),
+            ));
+        })
+    };
+
+    let on_algorithm_change = {
+        let query = query.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            query.set(select.value());
+        })
+    };
+
+    let on_policy_change = {
+        let policy = policy.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            policy.set(select.value());
         })
     };
 
+    let results = response.as_ref().map(|r| r.results.clone()).unwrap_or_default();
+    let algorithm_info = match response.as_ref().map(|r| (r.algorithm.as_str(), r.proposer.as_str())) {
+        Some(("egalitarian", _)) => "Algorithm: egalitarian stable matching (minimum total rank)",
+        Some(("minimum_regret", _)) => "Algorithm: minimum-regret stable matching (best worst rank)",
+        Some(("rank_maximal", _)) => "Algorithm: rank-maximal assignment (most first choices, then second...)",
+        Some(("min_cost", _)) => "Algorithm: minimum-cost assignment (Hungarian, lowest total rank)",
+        Some(("top_trading_cycles", _)) => "Algorithm: Top Trading Cycles (company priorities, Pareto-efficient for groups)",
+        Some(("lower_quotas", _)) => "Algorithm: lower quotas (every sponsor's minimum met where feasible)",
+        Some(("track_reserves", _)) => "Algorithm: Gale-Shapley with seats reserved per track",
+        Some(("serial_dictatorship", _)) => "Algorithm: random serial dictatorship (groups pick in random order)",
+        Some((_, "companies")) => "Algorithm: Gale-Shapley (companies propose, company-optimal)",
+        _ => "Algorithm: Gale-Shapley (groups propose, group-optimal)",
+    };
+
+    let modes = [
+        ("algorithm=gale_shapley&proposer=groups", "Group-optimal"),
+        ("algorithm=gale_shapley&proposer=companies", "Company-optimal"),
+        ("algorithm=egalitarian", "Egalitarian"),
+        ("algorithm=minimum_regret", "Minimum regret"),
+        ("algorithm=rank_maximal", "Rank-maximal"),
+        ("algorithm=min_cost", "Minimum cost"),
+        ("algorithm=lower_quotas", "Lower quotas"),
+        ("algorithm=track_reserves", "Track reservations"),
+        ("algorithm=serial_dictatorship", "Random serial dictatorship"),
+        ("algorithm=top_trading_cycles", "Top Trading Cycles"),
+        ("algorithm=top_trading_cycles&priority=score", "Top Trading Cycles (priority score)"),
+        ("ties=random", "Random tie-breaking"),
+        ("ties=lottery", "Lottery for company ties"),
+        ("ties=super_stable", "Super-stable (ties)"),
+        ("ties=strongly_stable", "Strongly stable (ties)"),
+    ];
+    let policies = [
+        ("leave", "Leave unmatched"),
+        ("free_acceptable", "Free acceptable seat"),
+        ("overflow", "Overflow (+1 seat)"),
+        ("random", "Random free seat"),
+    ];
+    let unmatched = response.as_ref().map(|r| r.unmatched.clone()).unwrap_or_default();
+    let seed = response.as_ref().and_then(|r| r.seed);
+    // Zwykły podgląd nie jest zapisywany, więc nie ma numeru przebiegu
+    let run = response.as_ref().and_then(|r| r.run_id.clone()).map(|id| format!(" (run {})", id)).unwrap_or_default();
+    let published = response.as_ref().is_some_and(|r| r.published);
+    let tie_report = response.as_ref().and_then(|r| r.tie_report.clone());
+    let quotas = response.as_ref().and_then(|r| r.quotas.clone());
+    let reservations = response.as_ref().and_then(|r| r.reservations.clone());
+    let comparison = response.as_ref().and_then(|r| r.comparison.clone()).unwrap_or_default();
+    let columns = comparison.iter().map(|p| p.group_ranks.len()).max().unwrap_or(0);
+
     html! {
         <div class="match-page">
             <div class="page-header">
@@ -86,6 +229,16 @@ pub fn match_page() -> Html {
             </div>
 
             <div class="controls">
+                <select onchange={on_algorithm_change}>
+                    { for modes.iter().map(|(value, label)| html! {
+                        <option value={*value} selected={*query == *value}>{ *label }</option>
+                    }) }
+                </select>
+                <select onchange={on_policy_change}>
+                    { for policies.iter().map(|(value, label)| html! {
+                        <option value={*value} selected={*policy == *value}>{ *label }</option>
+                    }) }
+                </select>
                 <button onclick={refresh_matches} class="btn refresh-btn">
                     {"Refresh"}
                 </button>
@@ -112,7 +265,48 @@ pub fn match_page() -> Html {
                 <div class="results-container">
                     <div class="results-header">
                         <h2>{ format!("Found {} matches", results.len()) }</h2>
-                        <p class="algorithm-info">{"Algorithm: Gale-Shapley"}</p>
+                        if published {
+                            <p class="publication official">{"Official results (published); the options above do not change them"}</p>
+                        } else {
+                            <p class="publication preview">{"Preview: results may still change until they are published"}</p>
+                        }
+                        <p class="algorithm-info">{ algorithm_info }</p>
+                        if let Some(seed) = seed {
+                            <p class="algorithm-info">{ format!("Random draws use seed {}{}", seed, run) }</p>
+                        }
+                        if let Some(report) = tie_report {
+                            <p class={classes!("tie-report", (!report.exists).then_some("missing"))}>{ report.message }</p>
+                        }
+                        if let Some(report) = quotas {
+                            if report.feasible {
+                                <p class="tie-report">{ format!("All minimum quotas met ({} blocking pairs)", report.blocking_pairs) }</p>
+                            } else {
+                                <div class="tie-report missing">
+                                    <p>{ "Minimum quotas cannot all be met. Smallest relaxation needed:" }</p>
+                                    <ul>
+                                        { for report.relaxations.iter().map(|r| html! {
+                                            <li>{ format!(
+                                                "{}{}: minimum {} → {}",
+                                                r.company,
+                                                r.project.as_ref().map(|p| format!(" / {}", p)).unwrap_or_default(),
+                                                r.minimum,
+                                                r.achievable
+                                            ) }</li>
+                                        }) }
+                                    </ul>
+                                </div>
+                            }
+                        }
+                        if let Some(report) = reservations {
+                            <div class="tie-report">
+                                <p>{ format!("Reserved seats ({} blocking pairs compared to plain stability):", report.blocking_pairs) }</p>
+                                <ul>
+                                    { for report.seats.iter().map(|r| html! {
+                                        <li>{ format!("{} · {}: {} of {} filled", r.company, r.track, r.filled, r.reserved) }</li>
+                                    }) }
+                                </ul>
+                            </div>
+                        }
                     </div>
 
                     <div class="matches-list">
@@ -123,12 +317,71 @@ pub fn match_page() -> Html {
                                     <span class="group">{ &m.group }</span>
                                     <span class="connector">{" ⇆ "}</span>
                                     <span class="company">{ &m.company }</span>
+                                    if m.project_title != m.company {
+                                        <span class="project" title={m.project.clone()}>{ format!(" / {}", m.project_title) }</span>
+                                    }
+                                    <div class="match-ranks">
+                                        { format!(
+                                            "Group's choice {} · company's choice {}",
+                                            rank_label(m.group_rank),
+                                            rank_label(m.company_rank)
+                                        ) }
+                                        if !m.mutually_acceptable {
+                                            <span class="not-acceptable">{ " · not mutually acceptable" }</span>
+                                        }
+                                    </div>
+                                    if !m.explanations.is_empty() {
+                                        <details class="explanations">
+                                            <summary>{ "Why not a higher choice?" }</summary>
+                                            <ul>
+                                                { for m.explanations.iter().map(|e| html! { <li>{ e }</li> }) }
+                                            </ul>
+                                        </details>
+                                    }
                                 </div>
-                                <div class="match-status">{"MATCHED"}</div>
+                                if m.pinned {
+                                    <div class="match-status pinned" title="Pinned by an administrator">{"PINNED"}</div>
+                                } else if m.fallback {
+                                    <div class="match-status fallback" title="Placed by the unmatched policy, not by the algorithm">{"FALLBACK"}</div>
+                                } else {
+                                    <div class="match-status">{"MATCHED"}</div>
+                                }
                             </div>
                         }) }
                     </div>
 
+                    if !unmatched.is_empty() {
+                        <p class="unmatched">{ format!("Unmatched: {}", unmatched.join(", ")) }</p>
+                    }
+
+                    if !comparison.is_empty() {
+                        <table class="rank-comparison">
+                            <caption>{ "Number of groups that got their #k choice" }</caption>
+                            <thead>
+                                <tr>
+                                    <th>{ "Algorithm" }</th>
+                                    { for (1..=columns).map(|k| html! { <th>{ format!("#{}", k) }</th> }) }
+                                    <th>{ "Outside preferences" }</th>
+                                    <th>{ "Unmatched" }</th>
+                                    <th>{ "Blocking pairs" }</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for comparison.iter().map(|p| html! {
+                                    <tr>
+                                        <td>{ &p.algorithm }</td>
+                                        { for (0..columns).map(|k| html! {
+                                            <td>{ p.group_ranks.get(k).copied().unwrap_or(0) }</td>
+                                        }) }
+                                        <td>{ p.outside_preferences }</td>
+                                        <td>{ p.unmatched }</td>
+                                        <td>{ p.blocking_pairs }</td>
+                                    </tr>
+                                }) }
+                            </tbody>
+                        </table>
+                    }
+
                     <div class="summary">
                         <p>{ "Each group is matched with their most preferred available company." }</p>
                     </div>